@@ -0,0 +1,104 @@
+//! Compares parsing a large generated passport file line by line into owned strings with
+//! parsing it from a single `Input` buffer read in one go.
+//!
+//! Run with `cargo bench --bench reader`.
+
+use advent_of_code_2020::reader::{read_lines, Input};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::hint::black_box;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const NUM_PASSPORTS: usize = 200_000;
+const NUM_RUNS: usize = 5;
+
+fn generate(path: &Path) -> Result<()> {
+    let mut file = std::io::BufWriter::new(fs::File::create(path)?);
+    for i in 0..NUM_PASSPORTS {
+        writeln!(file, "ecl:gry pid:{:09} eyr:2020 hcl:#fffffd", i)?;
+        writeln!(file, "byr:{} iyr:2017 cid:{}", 1920 + i % 80, i % 300)?;
+        writeln!(file, "hgt:{}cm", 150 + i % 40)?;
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+fn fields(line: &str) -> impl Iterator<Item = (&str, &str)> {
+    line.split_whitespace().filter_map(|f| f.split_once(':'))
+}
+
+/// The approach used before `Input`: a String per line and per key and value
+fn owned_lines(path: &Path) -> Result<usize> {
+    let mut passports = vec![HashMap::new()];
+    for line in read_lines(path)? {
+        let line = line?;
+        if line.is_empty() {
+            passports.push(HashMap::new());
+        }
+        for (key, value) in fields(&line) {
+            passports
+                .last_mut()
+                .unwrap()
+                .insert(key.to_owned(), value.to_owned());
+        }
+    }
+    Ok(passports.iter().map(HashMap::len).sum())
+}
+
+fn borrowed_records(input: &Input) -> usize {
+    input
+        .records()
+        .map(|r| {
+            r.lines
+                .iter()
+                .flat_map(|l| fields(l))
+                .collect::<HashMap<_, _>>()
+                .len()
+        })
+        .sum()
+}
+
+fn best_of<F: FnMut() -> Result<usize>>(mut f: F) -> Result<(Duration, usize)> {
+    let mut best = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..NUM_RUNS {
+        let start = Instant::now();
+        result = black_box(f()?);
+        best = best.min(start.elapsed());
+    }
+    Ok((best, result))
+}
+
+fn report<F: FnMut() -> Result<usize>>(name: &str, f: F) -> Result<()> {
+    let (time, num_fields) = best_of(f)?;
+    println!(
+        "{:<18} {:>8.1} ms ({} fields)",
+        name,
+        time.as_secs_f64() * 1e3,
+        num_fields,
+    );
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let path = std::env::temp_dir().join("aoc2020-reader-bench.txt");
+    generate(&path)?;
+    let size = fs::metadata(&path)?.len();
+    println!(
+        "{} passports, {:.1} MB, best of {} runs",
+        NUM_PASSPORTS,
+        size as f64 / 1e6,
+        NUM_RUNS,
+    );
+
+    report("owned lines", || owned_lines(&path))?;
+    report("Input::from_path", || {
+        Ok(borrowed_records(&Input::from_path(&path)?))
+    })?;
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
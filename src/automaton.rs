@@ -0,0 +1,432 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::coord::hex::Cube;
+use crate::coord::{Coord, CoordN};
+use crate::reader::Grid;
+
+/// States of a cell that count as alive for its neighbors
+pub trait Cell: Clone + PartialEq {
+    fn is_alive(&self) -> bool;
+}
+
+/// Decides the next state of a cell from its current state and its number of live neighbors
+pub trait Rule<S> {
+    fn next_state(&self, cell: &S, live_neighbors: usize) -> S;
+}
+
+/// Positions that influence a cell
+pub trait Neighborhood<P> {
+    fn for_each_neighbor<F: FnMut(P)>(&self, p: &P, f: F);
+}
+
+/// Storage for the cells of an automaton
+pub trait Storage: Clone + PartialEq {
+    type Pos;
+    type State;
+
+    /// Compute the next generation
+    fn step<N, R>(&self, neighborhood: &N, rule: &R) -> Self
+    where
+        N: Neighborhood<Self::Pos>,
+        R: Rule<Self::State>;
+
+    /// Number of live cells
+    fn population(&self) -> usize;
+
+    /// Whether the storage can follow the rule
+    fn supports<R: Rule<Self::State>>(_rule: &R) -> bool {
+        true
+    }
+}
+
+/// Life-like rule in B/S notation, like `B3/S23` for Conway's game of life. Counts above 9 can be
+/// given as comma separated lists, like `B3/S2,3,10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifeRule {
+    birth: HashSet<usize>,
+    survival: HashSet<usize>,
+}
+
+/// The 8 surrounding cells in 2D, or 3^N - 1 in N dimensions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Moore;
+
+/// Only the orthogonally adjacent cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VonNeumann;
+
+/// The 6 cells around a hex tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex;
+
+/// Generation where a repeating sequence of states starts and how long it is. A stable pattern
+/// has a period of 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+/// Cells together with the neighborhood and rule that evolve them
+#[derive(Debug, Clone)]
+pub struct Automaton<S, N, R> {
+    pub cells: S,
+    neighborhood: N,
+    rule: R,
+    generation: usize,
+}
+
+impl Cell for bool {
+    fn is_alive(&self) -> bool {
+        *self
+    }
+}
+
+impl<S, F> Rule<S> for F
+where
+    F: Fn(&S, usize) -> S,
+{
+    fn next_state(&self, cell: &S, live_neighbors: usize) -> S {
+        self(cell, live_neighbors)
+    }
+}
+
+impl LifeRule {
+    /// Whether dead cells with no live neighbors come to life, as in `B0`
+    pub fn births_on_zero(&self) -> bool {
+        self.birth.contains(&0)
+    }
+}
+
+impl Rule<bool> for LifeRule {
+    fn next_state(&self, cell: &bool, live_neighbors: usize) -> bool {
+        if *cell {
+            self.survival.contains(&live_neighbors)
+        } else {
+            self.birth.contains(&live_neighbors)
+        }
+    }
+}
+
+fn parse_counts(s: &str) -> Result<HashSet<usize>> {
+    if s.contains(',') {
+        s.split(',')
+            .map(|n| n.parse().with_context(|| format!("Invalid count {:?}", n)))
+            .collect()
+    } else {
+        s.chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as usize)
+                    .ok_or_else(|| anyhow!("Invalid count {:?}", c))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for LifeRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (birth, survival) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Expected a rule like B3/S23, got {:?}", s))?;
+        let birth = birth
+            .strip_prefix('B')
+            .ok_or_else(|| anyhow!("Birth counts must start with B in {:?}", s))?;
+        let survival = survival
+            .strip_prefix('S')
+            .ok_or_else(|| anyhow!("Survival counts must start with S in {:?}", s))?;
+        Ok(Self {
+            birth: parse_counts(birth)?,
+            survival: parse_counts(survival)?,
+        })
+    }
+}
+
+impl<P, F, I> Neighborhood<P> for F
+where
+    F: Fn(&P) -> I,
+    I: IntoIterator<Item = P>,
+{
+    fn for_each_neighbor<G: FnMut(P)>(&self, p: &P, f: G) {
+        self(p).into_iter().for_each(f)
+    }
+}
+
+impl Neighborhood<Coord> for Moore {
+    fn for_each_neighbor<F: FnMut(Coord)>(&self, p: &Coord, f: F) {
+        p.iter_all_neighbors().for_each(f)
+    }
+}
+
+impl<const N: usize> Neighborhood<CoordN<N>> for Moore {
+    fn for_each_neighbor<F: FnMut(CoordN<N>)>(&self, p: &CoordN<N>, f: F) {
+        p.iter_all_neighbors().for_each(f)
+    }
+}
+
+impl Neighborhood<Coord> for VonNeumann {
+    fn for_each_neighbor<F: FnMut(Coord)>(&self, p: &Coord, f: F) {
+        p.iter_neighbors().for_each(f)
+    }
+}
+
+impl<const N: usize> Neighborhood<CoordN<N>> for VonNeumann {
+    fn for_each_neighbor<F: FnMut(CoordN<N>)>(&self, p: &CoordN<N>, f: F) {
+        p.iter_neighbors().for_each(f)
+    }
+}
+
+impl Neighborhood<Cube> for Hex {
+    fn for_each_neighbor<F: FnMut(Cube)>(&self, p: &Cube, f: F) {
+        p.neighbors().for_each(f)
+    }
+}
+
+/// Dense storage where every cell of the grid is stored and cells outside of it are dead
+impl<S: Cell> Storage for Grid<S> {
+    type Pos = Coord;
+    type State = S;
+
+    fn step<N, R>(&self, neighborhood: &N, rule: &R) -> Self
+    where
+        N: Neighborhood<Coord>,
+        R: Rule<S>,
+    {
+        Grid::from_fn(self.width, self.height, |c| {
+            let mut live_neighbors = 0;
+            neighborhood.for_each_neighbor(&c, |n| {
+                if self.get(&n).is_some_and(Cell::is_alive) {
+                    live_neighbors += 1;
+                }
+            });
+            rule.next_state(self.get(&c).unwrap(), live_neighbors)
+        })
+    }
+
+    fn population(&self) -> usize {
+        self.tiles.iter().filter(|t| t.is_alive()).count()
+    }
+}
+
+/// Sparse storage of only the live cells on an unbounded space. Rules that bring cells with no
+/// live neighbors to life, like `B0`, aren't supported because they would fill the whole space.
+impl<P> Storage for HashSet<P>
+where
+    P: Clone + Eq + Hash,
+{
+    type Pos = P;
+    type State = bool;
+
+    fn step<N, R>(&self, neighborhood: &N, rule: &R) -> Self
+    where
+        N: Neighborhood<P>,
+        R: Rule<bool>,
+    {
+        let mut live_neighbors: HashMap<P, usize> = HashMap::new();
+        for p in self {
+            live_neighbors.entry(p.clone()).or_default();
+            neighborhood.for_each_neighbor(p, |n| *live_neighbors.entry(n).or_default() += 1);
+        }
+        live_neighbors
+            .into_iter()
+            .filter(|(p, n)| rule.next_state(&self.contains(p), *n))
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    fn population(&self) -> usize {
+        self.len()
+    }
+
+    fn supports<R: Rule<bool>>(rule: &R) -> bool {
+        !rule.next_state(&false, 0)
+    }
+}
+
+impl<S, N, R> Automaton<S, N, R>
+where
+    S: Storage,
+    N: Neighborhood<S::Pos>,
+    R: Rule<S::State>,
+{
+    /// Panics in debug builds if the storage doesn't support the rule
+    pub fn new(cells: S, neighborhood: N, rule: R) -> Self {
+        debug_assert!(
+            S::supports(&rule),
+            "The cell storage doesn't support this rule"
+        );
+        Self {
+            cells,
+            neighborhood,
+            rule,
+            generation: 0,
+        }
+    }
+
+    /// Number of steps taken so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.population()
+    }
+
+    fn next(&self, cells: &S) -> S {
+        cells.step(&self.neighborhood, &self.rule)
+    }
+
+    pub fn step(&mut self) {
+        self.cells = self.next(&self.cells);
+        self.generation += 1;
+    }
+
+    /// Take the given number of steps
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Step until the cells stop changing and return the number of steps taken. Never returns
+    /// if the cells don't settle.
+    pub fn run_until_stable(&mut self) -> usize {
+        let start = self.generation;
+        loop {
+            let next = self.next(&self.cells);
+            if next == self.cells {
+                return self.generation - start;
+            }
+            self.cells = next;
+            self.generation += 1;
+        }
+    }
+
+    /// Look for a repeating sequence of states from the current generation on, without changing
+    /// the cells. Gives up after the given number of steps. The start of the cycle is relative to
+    /// the current generation.
+    pub fn find_cycle(&self, max_steps: usize) -> Option<Cycle> {
+        // Brent's algorithm which only needs to keep two states around
+        let mut power = 1;
+        let mut period = 1;
+        let mut tortoise = self.cells.clone();
+        let mut hare = self.next(&tortoise);
+        let mut steps = 1;
+        while tortoise != hare {
+            if steps >= max_steps {
+                return None;
+            }
+            if power == period {
+                tortoise = hare.clone();
+                power *= 2;
+                period = 0;
+            }
+            hare = self.next(&hare);
+            period += 1;
+            steps += 1;
+        }
+
+        let mut tortoise = self.cells.clone();
+        let mut hare = self.cells.clone();
+        for _ in 0..period {
+            hare = self.next(&hare);
+        }
+        let mut start = 0;
+        while tortoise != hare {
+            tortoise = self.next(&tortoise);
+            hare = self.next(&hare);
+            start += 1;
+        }
+        Some(Cycle { start, period })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(lines: &[&str]) -> Result<Grid<bool>> {
+        Ok(Grid::<char>::from_lines(lines)?.map(|c| *c == '#'))
+    }
+
+    #[test]
+    fn test_rule_strings() -> Result<()> {
+        let life: LifeRule = "B3/S23".parse()?;
+        assert!(life.next_state(&false, 3));
+        assert!(!life.next_state(&false, 2));
+        assert!(life.next_state(&true, 2));
+        assert!(!life.next_state(&true, 4));
+
+        let large: LifeRule = "B3/S2,3,10".parse()?;
+        assert!(large.next_state(&true, 10));
+        assert!(!large.births_on_zero());
+        assert!("B03/S23".parse::<LifeRule>()?.births_on_zero());
+        assert!("B3S23".parse::<LifeRule>().is_err());
+        assert!("B3/Sx".parse::<LifeRule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dense() -> Result<()> {
+        let blinker = life(&[".....", "..#..", "..#..", "..#..", "....."])?;
+        let mut automaton = Automaton::new(blinker.clone(), Moore, "B3/S23".parse::<LifeRule>()?);
+        automaton.step();
+        assert_eq!(
+            automaton.cells,
+            life(&[".....", ".....", ".###.", ".....", "....."])?,
+        );
+        assert_eq!(automaton.population(), 3);
+        assert_eq!(
+            automaton.find_cycle(10),
+            Some(Cycle {
+                start: 0,
+                period: 2
+            }),
+        );
+        automaton.run(3);
+        assert_eq!(automaton.generation(), 4);
+        assert_eq!(automaton.cells, blinker);
+
+        let block = life(&["....", ".##.", ".##.", "...."])?;
+        let mut automaton = Automaton::new(block, Moore, "B3/S23".parse::<LifeRule>()?);
+        assert_eq!(automaton.run_until_stable(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse() -> Result<()> {
+        // A glider never repeats on an unbounded plane, but always has 5 cells
+        let glider: HashSet<Coord> = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+            .into_iter()
+            .map(|(x, y)| Coord::new(x, y))
+            .collect();
+        let mut automaton = Automaton::new(glider.clone(), Moore, "B3/S23".parse::<LifeRule>()?);
+        assert_eq!(automaton.find_cycle(20), None);
+        automaton.run(4);
+        let moved = glider
+            .iter()
+            .map(|c| c + Coord::new(1, 1))
+            .collect::<HashSet<_>>();
+        assert_eq!(automaton.cells, moved);
+
+        // A single cell that dies settles after one step
+        let single: HashSet<_> = vec![CoordN([0, 0, 0])].into_iter().collect();
+        let mut automaton = Automaton::new(single, Moore, |alive: &bool, n: usize| *alive && n > 0);
+        assert_eq!(automaton.run_until_stable(), 1);
+        assert_eq!(automaton.population(), 0);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "doesn't support this rule")]
+    fn test_sparse_births_on_zero() {
+        let single: HashSet<_> = vec![Coord::new(0, 0)].into_iter().collect();
+        Automaton::new(single, Moore, "B0/S".parse::<LifeRule>().unwrap());
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+pub mod hex;
+
+use anyhow::{anyhow, Result};
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub, SubAssign};
 
 use self::Direction::*;
 
@@ -8,13 +11,59 @@ pub struct Coord {
     pub y: isize,
 }
 
+/// Coordinate with N dimensions
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct CoordN<const N: usize>(pub [isize; N]);
+
+/// Iterator over the 2N orthogonal neighbors of a coordinate
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoordNNeighbors<const N: usize> {
+    origin: CoordN<N>,
+    i: usize,
+}
+
+/// Iterator over the 3^N - 1 neighbors of a coordinate, including diagonals (Moore neighborhood)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoordNAllNeighbors<const N: usize> {
+    origin: CoordN<N>,
+    i: usize,
+}
+
+/// Iterator over all coordinates within an inclusive bounding box
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoordNRange<const N: usize> {
+    min: CoordN<N>,
+    max: CoordN<N>,
+    next: Option<CoordN<N>>,
+}
+
+/// Rectangle between two inclusive corners. A rectangle where max is smaller than min on either
+/// axis is empty.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Rect {
+    pub min: Coord,
+    pub max: Coord,
+}
+
+/// Box with N dimensions between two inclusive corners. Like `Rect`, it's empty if max is smaller
+/// than min on any axis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BoundingBox<const N: usize> {
+    pub min: CoordN<N>,
+    pub max: CoordN<N>,
+}
+
 /// Coordinate system assumes down is positive and up is negative
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Direction {
     Up(usize),
+    UpRight(usize),
     Right(usize),
+    DownRight(usize),
     Down(usize),
+    DownLeft(usize),
     Left(usize),
+    UpLeft(usize),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -51,17 +100,33 @@ impl Coord {
     }
 
     pub fn offset(&self, dir: Direction) -> Self {
-        match dir {
-            Up(i) => Coord::new(self.x, self.y - i as isize),
-            Right(i) => Coord::new(self.x + i as isize, self.y),
-            Down(i) => Coord::new(self.x, self.y + i as isize),
-            Left(i) => Coord::new(self.x - i as isize, self.y),
-        }
+        let unit = dir.unit();
+        let len = dir.len() as isize;
+        Coord::new(self.x + unit.x * len, self.y + unit.y * len)
     }
 
     pub fn offset_mut(&mut self, dir: Direction) {
         *self = self.offset(dir);
     }
+
+    /// Rotate around the origin. Positive degrees are clockwise (as seen on screen with y
+    /// pointing down) and must be a multiple of 90.
+    pub fn rotate(&self, degrees: isize) -> Result<Self> {
+        if degrees % 90 != 0 {
+            return Err(anyhow!("Degrees must be a multiple of 90, got {}", degrees));
+        }
+        Ok(match (degrees / 90).rem_euclid(4) {
+            0 => *self,
+            1 => Coord::new(-self.y, self.x),
+            2 => Coord::new(-self.x, -self.y),
+            _ => Coord::new(self.y, -self.x),
+        })
+    }
+
+    /// Rotate around the given center, see `rotate`
+    pub fn rotate_around(&self, center: &Coord, degrees: isize) -> Result<Self> {
+        Ok((self - center).rotate(degrees)? + center)
+    }
 }
 
 macro_rules! coord_op_overload {
@@ -117,44 +182,511 @@ coord_op_overload!(&Coord, &Coord);
 coord_assign_op_overload!(Coord, Coord);
 coord_assign_op_overload!(Coord, &Coord);
 
+impl From<Coord> for CoordN<2> {
+    fn from(c: Coord) -> Self {
+        CoordN([c.x, c.y])
+    }
+}
+
+impl From<CoordN<2>> for Coord {
+    fn from(c: CoordN<2>) -> Self {
+        Coord::new(c[0], c[1])
+    }
+}
+
+impl<const N: usize> CoordN<N> {
+    pub fn new(components: [isize; N]) -> Self {
+        CoordN(components)
+    }
+
+    pub fn origin() -> Self {
+        CoordN([0; N])
+    }
+
+    /// Coordinate where all components have the same value
+    pub fn splat(v: isize) -> Self {
+        CoordN([v; N])
+    }
+
+    /// Sum of the absolute differences along each axis (taxicab distance)
+    pub fn manhattan_distance(a: Self, b: Self) -> usize {
+        (0..N).map(|i| (a[i] - b[i]).unsigned_abs()).sum()
+    }
+
+    /// Largest absolute difference along any axis (chessboard distance)
+    pub fn chebyshev_distance(a: Self, b: Self) -> usize {
+        (0..N)
+            .map(|i| (a[i] - b[i]).unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Component-wise minimum
+    pub fn min(self, other: Self) -> Self {
+        let mut out = self;
+        for i in 0..N {
+            out[i] = out[i].min(other[i]);
+        }
+        out
+    }
+
+    /// Component-wise maximum
+    pub fn max(self, other: Self) -> Self {
+        let mut out = self;
+        for i in 0..N {
+            out[i] = out[i].max(other[i]);
+        }
+        out
+    }
+
+    /// Return the smallest and largest coordinates of the box containing all given coordinates
+    pub fn min_max_bounds<I>(coords: I) -> Option<(Self, Self)>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        coords.into_iter().fold(None, |bounds, c| match bounds {
+            Some((min, max)) => Some((c.min(min), c.max(max))),
+            None => Some((c, c)),
+        })
+    }
+
+    pub fn iter_neighbors(&self) -> CoordNNeighbors<N> {
+        CoordNNeighbors {
+            origin: *self,
+            i: 0,
+        }
+    }
+
+    pub fn iter_all_neighbors(&self) -> CoordNAllNeighbors<N> {
+        CoordNAllNeighbors {
+            origin: *self,
+            i: 0,
+        }
+    }
+
+    /// Iterate over all coordinates between min and max (inclusive). The last axis changes
+    /// fastest.
+    pub fn iter_box(min: Self, max: Self) -> CoordNRange<N> {
+        let is_empty = (0..N).any(|i| min[i] > max[i]);
+        CoordNRange {
+            min,
+            max,
+            next: if is_empty { None } else { Some(min) },
+        }
+    }
+}
+
+impl<const N: usize> Default for CoordN<N> {
+    fn default() -> Self {
+        Self::origin()
+    }
+}
+
+impl<const N: usize> Index<usize> for CoordN<N> {
+    type Output = isize;
+    fn index(&self, i: usize) -> &isize {
+        &self.0[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for CoordN<N> {
+    fn index_mut(&mut self, i: usize) -> &mut isize {
+        &mut self.0[i]
+    }
+}
+
+impl<const N: usize> Add for CoordN<N> {
+    type Output = Self;
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<const N: usize> Sub for CoordN<N> {
+    type Output = Self;
+    fn sub(mut self, rhs: Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for CoordN<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for i in 0..N {
+            self[i] += rhs[i];
+        }
+    }
+}
+
+impl<const N: usize> SubAssign for CoordN<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for i in 0..N {
+            self[i] -= rhs[i];
+        }
+    }
+}
+
+impl<const N: usize> Mul<isize> for CoordN<N> {
+    type Output = Self;
+    fn mul(mut self, rhs: isize) -> Self {
+        for i in 0..N {
+            self[i] *= rhs;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Iterator for CoordNNeighbors<N> {
+    type Item = CoordN<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i >= 2 * N {
+            return None;
+        }
+        let mut output = self.origin;
+        output[self.i / 2] += if self.i.is_multiple_of(2) { -1 } else { 1 };
+        self.i += 1;
+        Some(output)
+    }
+}
+
+impl<const N: usize> Iterator for CoordNAllNeighbors<N> {
+    type Item = CoordN<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Each neighbor is encoded as a number in base 3 where each digit is the offset along
+        // one axis plus one. The middle number is the origin itself.
+        let num_offsets = 3usize.pow(N as u32);
+        if self.i == num_offsets / 2 {
+            self.i += 1;
+        }
+        if self.i >= num_offsets {
+            return None;
+        }
+
+        let mut output = self.origin;
+        let mut code = self.i;
+        for axis in (0..N).rev() {
+            output[axis] += (code % 3) as isize - 1;
+            code /= 3;
+        }
+        self.i += 1;
+        Some(output)
+    }
+}
+
+impl<const N: usize> Iterator for CoordNRange<N> {
+    type Item = CoordN<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let output = self.next?;
+
+        // Increment like an odometer, starting with the last axis
+        let mut next = output;
+        self.next = None;
+        for axis in (0..N).rev() {
+            if next[axis] < self.max[axis] {
+                next[axis] += 1;
+                self.next = Some(next);
+                break;
+            }
+            next[axis] = self.min[axis];
+        }
+        Some(output)
+    }
+}
+
+impl Rect {
+    pub fn new(min: Coord, max: Coord) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(Coord::origin(), Coord::new(-1, -1))
+    }
+
+    /// Rectangle of the given size with its top left corner at the given coordinate
+    pub fn from_size(origin: Coord, width: usize, height: usize) -> Self {
+        Self::new(
+            origin,
+            origin + Coord::new(width as isize - 1, height as isize - 1),
+        )
+    }
+
+    /// Smallest rectangle containing all the given coordinates
+    pub fn from_coords<I>(coords: I) -> Self
+    where
+        I: IntoIterator<Item = Coord>,
+    {
+        coords
+            .into_iter()
+            .fold(Self::empty(), |rect, c| rect.union(&Self::new(c, c)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max.x < self.min.x || self.max.y < self.min.y
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1).max(0) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1).max(0) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, c: &Coord) -> bool {
+        (self.min.x..=self.max.x).contains(&c.x) && (self.min.y..=self.max.y).contains(&c.y)
+    }
+
+    /// Grow the rectangle by the given amount on every side. Negative amounts shrink it.
+    pub fn pad(&self, n: isize) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        Self::new(self.min - Coord::new(n, n), self.max + Coord::new(n, n))
+    }
+
+    /// Smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            *other
+        } else if other.is_empty() {
+            *self
+        } else {
+            Self::new(
+                Coord::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+                Coord::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+            )
+        }
+    }
+
+    /// Rectangle covered by both rectangles, which may be empty
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(
+            Coord::new(self.min.x.max(other.min.x), self.min.y.max(other.min.y)),
+            Coord::new(self.max.x.min(other.max.x), self.max.y.min(other.max.y)),
+        )
+    }
+
+    /// Iterate over every coordinate in the rectangle row by row
+    pub fn iter(&self) -> impl Iterator<Item = Coord> {
+        let Self { min, max } = *self;
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Coord::new(x, y)))
+    }
+}
+
+impl<const N: usize> BoundingBox<N> {
+    pub fn new(min: CoordN<N>, max: CoordN<N>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(CoordN::origin(), CoordN::splat(-1))
+    }
+
+    /// Smallest box containing all the given coordinates
+    pub fn from_coords<I>(coords: I) -> Self
+    where
+        I: IntoIterator<Item = CoordN<N>>,
+    {
+        match CoordN::min_max_bounds(coords) {
+            Some((min, max)) => Self::new(min, max),
+            None => Self::empty(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|i| self.max[i] < self.min[i])
+    }
+
+    /// Length of the box along the given axis
+    pub fn size(&self, axis: usize) -> usize {
+        (self.max[axis] - self.min[axis] + 1).max(0) as usize
+    }
+
+    pub fn volume(&self) -> usize {
+        (0..N).map(|i| self.size(i)).product()
+    }
+
+    pub fn contains(&self, c: &CoordN<N>) -> bool {
+        (0..N).all(|i| (self.min[i]..=self.max[i]).contains(&c[i]))
+    }
+
+    /// Grow the box by the given amount on every side. Negative amounts shrink it.
+    pub fn pad(&self, n: isize) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        Self::new(self.min - CoordN::splat(n), self.max + CoordN::splat(n))
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            *other
+        } else if other.is_empty() {
+            *self
+        } else {
+            Self::new(self.min.min(other.min), self.max.max(other.max))
+        }
+    }
+
+    /// Box covered by both boxes, which may be empty
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    /// Iterate over every coordinate in the box with the last axis changing fastest
+    pub fn iter(&self) -> CoordNRange<N> {
+        CoordN::iter_box(self.min, self.max)
+    }
+}
+
+impl From<Rect> for BoundingBox<2> {
+    fn from(r: Rect) -> Self {
+        Self::new(r.min.into(), r.max.into())
+    }
+}
+
 impl Direction {
-    pub fn resize(&self, len: usize) -> Direction {
+    /// All eight directions with the given length, clockwise starting with up
+    pub fn all(len: usize) -> impl Iterator<Item = Self> {
+        (0..8).map(move |i| Self::from_index(i, len))
+    }
+
+    /// The four directions along the axes with the given length, clockwise starting with up
+    pub fn cardinals(len: usize) -> impl Iterator<Item = Self> {
+        (0..8).step_by(2).map(move |i| Self::from_index(i, len))
+    }
+
+    /// Direction from the number of 45 degree steps clockwise from up
+    fn from_index(i: usize, len: usize) -> Self {
+        match i % 8 {
+            0 => Up(len),
+            1 => UpRight(len),
+            2 => Right(len),
+            3 => DownRight(len),
+            4 => Down(len),
+            5 => DownLeft(len),
+            6 => Left(len),
+            _ => UpLeft(len),
+        }
+    }
+
+    fn index(&self) -> usize {
         match self {
-            Up(_) => Up(len),
-            Right(_) => Right(len),
-            Down(_) => Down(len),
-            Left(_) => Left(len),
+            Up(_) => 0,
+            UpRight(_) => 1,
+            Right(_) => 2,
+            DownRight(_) => 3,
+            Down(_) => 4,
+            DownLeft(_) => 5,
+            Left(_) => 6,
+            UpLeft(_) => 7,
+        }
+    }
+
+    /// Compass heading where up is 0 degrees and right is 90 degrees. Degrees must be a multiple
+    /// of 45.
+    pub fn from_degrees(degrees: isize, len: usize) -> Result<Self> {
+        Up(len).rotate(degrees)
+    }
+
+    pub fn degrees(&self) -> isize {
+        self.index() as isize * 45
+    }
+
+    /// Direction of the given vector if it's along an axis or a diagonal. The length is the
+    /// number of steps in that direction.
+    pub fn from_coord(c: Coord) -> Option<Self> {
+        let len = c.x.unsigned_abs().max(c.y.unsigned_abs());
+        if len == 0 || (c.x != 0 && c.y != 0 && c.x.abs() != c.y.abs()) {
+            return None;
         }
+        let unit = Coord::new(c.x.signum(), c.y.signum());
+        Self::all(len).find(|d| d.unit() == unit)
+    }
+
+    /// Vector of length one in this direction, diagonals move one step along both axes
+    pub fn unit(&self) -> Coord {
+        let (x, y) = match self {
+            Up(_) => (0, -1),
+            UpRight(_) => (1, -1),
+            Right(_) => (1, 0),
+            DownRight(_) => (1, 1),
+            Down(_) => (0, 1),
+            DownLeft(_) => (-1, 1),
+            Left(_) => (-1, 0),
+            UpLeft(_) => (-1, -1),
+        };
+        Coord::new(x, y)
+    }
+
+    /// Vector of this direction scaled by its length
+    pub fn to_coord(&self) -> Coord {
+        Coord::origin().offset(*self)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    pub fn resize(&self, len: usize) -> Direction {
+        Self::from_index(self.index(), len)
     }
 
     pub fn len(&self) -> usize {
         match self {
-            Up(n) => *n,
-            Right(n) => *n,
-            Down(n) => *n,
-            Left(n) => *n,
+            Up(n) | UpRight(n) | Right(n) | DownRight(n) | Down(n) | DownLeft(n) | Left(n)
+            | UpLeft(n) => *n,
         }
     }
 
-    pub fn turn_left(&self) -> Self {
-        match self {
-            Self::Up(n) => Self::Left(*n),
-            Self::Right(n) => Self::Up(*n),
-            Self::Down(n) => Self::Right(*n),
-            Self::Left(n) => Self::Down(*n),
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rotate clockwise by the given number of degrees, which must be a multiple of 45.
+    /// Negative degrees rotate counter-clockwise.
+    pub fn rotate(&self, degrees: isize) -> Result<Self> {
+        if degrees % 45 != 0 {
+            return Err(anyhow!("Degrees must be a multiple of 45, got {}", degrees));
         }
+        let steps = (degrees / 45).rem_euclid(8) as usize;
+        Ok(Self::from_index(self.index() + steps, self.len()))
+    }
+
+    pub fn turn_left(&self) -> Self {
+        Self::from_index(self.index() + 6, self.len())
     }
 
     pub fn turn_right(&self) -> Self {
-        match self {
-            Self::Up(n) => Self::Right(*n),
-            Self::Right(n) => Self::Down(*n),
-            Self::Down(n) => Self::Left(*n),
-            Self::Left(n) => Self::Up(*n),
-        }
+        Self::from_index(self.index() + 2, self.len())
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::from_index(self.index() + 4, self.len())
     }
 }
 
+/// Offsets to the 8 neighbors, clockwise starting from up. The even ones are orthogonal.
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
 impl CoordNeighbors {
     pub fn new(origin: Coord) -> Self {
         Self {
@@ -177,20 +709,9 @@ impl Iterator for CoordNeighbors {
     type Item = Coord;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let output = match self.i {
-            0 => Some(self.origin.offset(Up(1))),
-            1 => Some(self.origin.offset(Up(1)).offset(Right(1))),
-            2 => Some(self.origin.offset(Right(1))),
-            3 => Some(self.origin.offset(Right(1)).offset(Down(1))),
-            4 => Some(self.origin.offset(Down(1))),
-            5 => Some(self.origin.offset(Down(1)).offset(Left(1))),
-            6 => Some(self.origin.offset(Left(1))),
-            7 => Some(self.origin.offset(Left(1)).offset(Up(1))),
-            _ => None,
-        };
+        let (x, y) = *NEIGHBOR_OFFSETS.get(self.i)?;
         self.i += self.step;
-
-        output
+        Some(Coord::new(self.origin.x + x, self.origin.y + y))
     }
 }
 
@@ -227,6 +748,105 @@ mod tests {
         assert_eq!(Coord::origin().offset(Left(100)), Coord::new(-100, 0));
     }
 
+    #[test]
+    fn test_coord_n() {
+        let a = CoordN([1, -2, 3]);
+        let b = CoordN([4, 2, 3]);
+        assert_eq!(a + b, CoordN([5, 0, 6]));
+        assert_eq!(a - b, CoordN([-3, -4, 0]));
+        assert_eq!(a * 2, CoordN([2, -4, 6]));
+        assert_eq!(CoordN::manhattan_distance(a, b), 7);
+        assert_eq!(CoordN::chebyshev_distance(a, b), 4);
+        assert_eq!(CoordN::from(Coord::new(1, 2)), CoordN([1, 2]));
+        assert_eq!(Coord::from(CoordN([1, 2])), Coord::new(1, 2));
+    }
+
+    #[test]
+    fn test_coord_n_neighbors() {
+        let origin = CoordN::<3>::origin();
+        assert_eq!(origin.iter_neighbors().count(), 6);
+        assert!(origin
+            .iter_neighbors()
+            .all(|n| CoordN::manhattan_distance(n, origin) == 1));
+
+        let all = origin.iter_all_neighbors().collect::<Vec<_>>();
+        assert_eq!(all.len(), 26);
+        assert!(!all.contains(&origin));
+        assert!(all
+            .iter()
+            .all(|n| CoordN::chebyshev_distance(*n, origin) == 1));
+
+        assert_eq!(CoordN::<4>::origin().iter_all_neighbors().count(), 80);
+    }
+
+    #[test]
+    fn test_coord_n_box() {
+        assert_eq!(
+            CoordN::iter_box(CoordN([0, 0]), CoordN([1, 2])).collect::<Vec<_>>(),
+            vec![
+                CoordN([0, 0]),
+                CoordN([0, 1]),
+                CoordN([0, 2]),
+                CoordN([1, 0]),
+                CoordN([1, 1]),
+                CoordN([1, 2]),
+            ],
+        );
+        assert_eq!(CoordN::iter_box(CoordN([1]), CoordN([0])).count(), 0);
+        assert_eq!(
+            CoordN::min_max_bounds(vec![CoordN([1, 5]), CoordN([3, -2])]),
+            Some((CoordN([1, -2]), CoordN([3, 5]))),
+        );
+    }
+
+    #[test]
+    fn test_diagonal_offset() {
+        assert_eq!(Coord::origin().offset(UpRight(3)), Coord::new(3, -3));
+        assert_eq!(Coord::origin().offset(DownLeft(2)), Coord::new(-2, 2));
+    }
+
+    #[test]
+    fn test_direction_rotate() -> Result<()> {
+        assert_eq!(Up(2).rotate(45)?, UpRight(2));
+        assert_eq!(Up(2).rotate(-45)?, UpLeft(2));
+        assert_eq!(Left(1).rotate(450)?, Up(1));
+        assert!(Up(1).rotate(30).is_err());
+        assert_eq!(Right(1).turn_left(), Up(1));
+        assert_eq!(UpLeft(1).turn_right(), UpRight(1));
+        assert_eq!(Direction::from_degrees(135, 1)?, DownRight(1));
+        assert_eq!(DownLeft(1).degrees(), 225);
+        Ok(())
+    }
+
+    #[test]
+    fn test_direction_coord() {
+        assert_eq!(Direction::from_coord(Coord::new(-4, 4)), Some(DownLeft(4)));
+        assert_eq!(Direction::from_coord(Coord::new(0, -2)), Some(Up(2)));
+        assert_eq!(Direction::from_coord(Coord::new(1, 2)), None);
+        assert_eq!(Direction::from_coord(Coord::origin()), None);
+        assert_eq!(UpLeft(2).to_coord(), Coord::new(-2, -2));
+        assert_eq!(Direction::all(1).count(), 8);
+        assert_eq!(
+            Direction::cardinals(1).filter(|d| d.is_diagonal()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_coord_rotate() -> Result<()> {
+        let c = Coord::new(10, -4);
+        assert_eq!(c.rotate(90)?, Coord::new(4, 10));
+        assert_eq!(c.rotate(-90)?, Coord::new(-4, -10));
+        assert_eq!(c.rotate(180)?, Coord::new(-10, 4));
+        assert_eq!(c.rotate(360)?, c);
+        assert!(c.rotate(45).is_err());
+        assert_eq!(
+            Coord::new(3, 1).rotate_around(&Coord::new(1, 1), 90)?,
+            Coord::new(1, 3),
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_neighbors() {
         assert_eq!(
@@ -256,4 +876,67 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_rect() {
+        let r = Rect::from_coords(vec![Coord::new(1, 5), Coord::new(3, -2)]);
+        assert_eq!(r, Rect::new(Coord::new(1, -2), Coord::new(3, 5)));
+        assert_eq!((r.width(), r.height(), r.area()), (3, 8, 24));
+        assert!(r.contains(&Coord::new(2, 0)));
+        assert!(!r.contains(&Coord::new(0, 0)));
+        assert_eq!(r.pad(1).area(), 50);
+
+        let other = Rect::from_size(Coord::new(2, 4), 5, 5);
+        assert_eq!(
+            r.intersection(&other),
+            Rect::new(Coord::new(2, 4), Coord::new(3, 5)),
+        );
+        assert_eq!(
+            r.union(&other),
+            Rect::new(Coord::new(1, -2), Coord::new(6, 8)),
+        );
+        assert!(r
+            .intersection(&Rect::from_size(Coord::new(10, 10), 2, 2))
+            .is_empty());
+        assert_eq!(
+            Rect::from_size(Coord::origin(), 2, 2)
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![
+                Coord::new(0, 0),
+                Coord::new(1, 0),
+                Coord::new(0, 1),
+                Coord::new(1, 1),
+            ],
+        );
+
+        let empty = Rect::from_coords(None);
+        assert!(empty.is_empty());
+        assert_eq!(empty.area(), 0);
+        assert_eq!(empty.pad(2), empty);
+        assert_eq!(empty.union(&r), r);
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let b = BoundingBox::from_coords(vec![CoordN([0, 0, 0]), CoordN([1, 2, -1])]);
+        assert_eq!(b.volume(), 12);
+        assert_eq!(b.iter().count(), 12);
+        assert_eq!(b.pad(1).volume(), 4 * 5 * 4);
+        assert!(b.contains(&CoordN([1, 1, -1])));
+        assert!(!b.contains(&CoordN([1, 1, 1])));
+
+        let other = BoundingBox::new(CoordN([1, 1, 0]), CoordN([5, 5, 5]));
+        assert_eq!(b.intersection(&other).volume(), 2);
+        assert_eq!(
+            b.union(&other),
+            BoundingBox::new(CoordN([0, 0, -1]), CoordN([5, 5, 5])),
+        );
+        assert!(BoundingBox::<4>::from_coords(None).is_empty());
+        assert_eq!(
+            BoundingBox::from(Rect::from_size(Coord::origin(), 2, 3)).volume(),
+            6,
+        );
+    }
 }
@@ -0,0 +1,474 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Hex coordinate in cube form. The invariant `q + r + s == 0` always holds.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cube {
+    pub q: isize,
+    pub r: isize,
+    pub s: isize,
+}
+
+/// Hex coordinate in axial form, which is cube form with `s` left out
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Axial {
+    pub q: isize,
+    pub r: isize,
+}
+
+/// Pointy-top hexes have neighbors to the east and west, flat-top hexes have neighbors to the
+/// north and south
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Orientation {
+    PointyTop,
+    FlatTop,
+}
+
+/// Compass direction to a neighboring hex. Which six directions are valid depends on the
+/// orientation of the grid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum HexDirection {
+    N,
+    Ne,
+    E,
+    Se,
+    S,
+    Sw,
+    W,
+    Nw,
+}
+
+/// Unit vectors to the six neighbors, counter-clockwise
+const CUBE_DIRECTIONS: [(isize, isize, isize); 6] = [
+    (1, 0, -1),
+    (1, -1, 0),
+    (0, -1, 1),
+    (-1, 0, 1),
+    (-1, 1, 0),
+    (0, 1, -1),
+];
+
+/// Names of `CUBE_DIRECTIONS` for each orientation
+const POINTY_TOP_DIRECTIONS: [HexDirection; 6] = [
+    HexDirection::E,
+    HexDirection::Ne,
+    HexDirection::Nw,
+    HexDirection::W,
+    HexDirection::Sw,
+    HexDirection::Se,
+];
+const FLAT_TOP_DIRECTIONS: [HexDirection; 6] = [
+    HexDirection::Se,
+    HexDirection::Ne,
+    HexDirection::N,
+    HexDirection::Nw,
+    HexDirection::Sw,
+    HexDirection::S,
+];
+
+impl Cube {
+    /// Create a cube coordinate from q and r, s is derived from them
+    pub fn new(q: isize, r: isize) -> Self {
+        Self { q, r, s: -q - r }
+    }
+
+    pub fn origin() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// Neighbor in the given direction index (0-5, counter-clockwise)
+    fn unit(i: usize) -> Self {
+        let (q, r, s) = CUBE_DIRECTIONS[i % 6];
+        Self { q, r, s }
+    }
+
+    pub fn distance(a: Self, b: Self) -> usize {
+        let d = a - b;
+        (d.q.unsigned_abs() + d.r.unsigned_abs() + d.s.unsigned_abs()) / 2
+    }
+
+    pub fn distance_from_origin(&self) -> usize {
+        Self::distance(*self, Self::origin())
+    }
+
+    /// Neighbor in the given direction, or None if the direction isn't valid for the orientation
+    pub fn neighbor(&self, dir: HexDirection, orientation: Orientation) -> Option<Self> {
+        Some(*self + dir.offset(orientation)?)
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = Self> {
+        let origin = *self;
+        (0..6).map(move |i| origin + Self::unit(i))
+    }
+
+    /// Follow all directions starting from this coordinate
+    pub fn follow(&self, dirs: &[HexDirection], orientation: Orientation) -> Result<Self> {
+        dirs.iter().try_fold(*self, |c, dir| {
+            c.neighbor(*dir, orientation)
+                .ok_or_else(|| anyhow!("{} is not valid for {:?} hexes", dir, orientation))
+        })
+    }
+
+    /// All hexes at exactly the given distance from the center
+    pub fn ring(center: Self, radius: usize) -> Vec<Self> {
+        if radius == 0 {
+            return vec![center];
+        }
+
+        let mut out = Vec::with_capacity(6 * radius);
+        let mut hex = center + Self::unit(4) * radius as isize;
+        for i in 0..6 {
+            for _ in 0..radius {
+                out.push(hex);
+                hex += Self::unit(i);
+            }
+        }
+        out
+    }
+
+    /// All hexes within the given distance from the center, ordered ring by ring outwards
+    pub fn spiral(center: Self, radius: usize) -> Vec<Self> {
+        (0..=radius).flat_map(|r| Self::ring(center, r)).collect()
+    }
+
+    /// Round fractional cube coordinates to the closest hex
+    fn round(q: f64, r: f64, s: f64) -> Self {
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        Self::new(rq as isize, rr as isize)
+    }
+
+    /// All hexes on the straight line between a and b, including both ends
+    pub fn line(a: Self, b: Self) -> Vec<Self> {
+        let n = Self::distance(a, b);
+        if n == 0 {
+            return vec![a];
+        }
+
+        // Nudge the end points slightly so that points exactly on an edge are rounded
+        // consistently
+        let lerp = |a: isize, b: isize, t: f64, e: f64| a as f64 + 1e-6 * e + (b - a) as f64 * t;
+        (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                Self::round(
+                    lerp(a.q, b.q, t, 1.0),
+                    lerp(a.r, b.r, t, 1.0),
+                    lerp(a.s, b.s, t, -2.0),
+                )
+            })
+            .collect()
+    }
+
+    /// Rotate 60 degrees clockwise (as seen on screen with y down) around the given center
+    pub fn rotate_right(&self, center: Self) -> Self {
+        let d = *self - center;
+        center
+            + Self {
+                q: -d.r,
+                r: -d.s,
+                s: -d.q,
+            }
+    }
+
+    /// Rotate 60 degrees counter-clockwise around the given center
+    pub fn rotate_left(&self, center: Self) -> Self {
+        let d = *self - center;
+        center
+            + Self {
+                q: -d.s,
+                r: -d.q,
+                s: -d.r,
+            }
+    }
+
+    /// Center of the hex in pixels for hexes with the given outer radius, with y pointing down
+    pub fn to_pixel(&self, size: f64, orientation: Orientation) -> (f64, f64) {
+        let sqrt_3 = 3f64.sqrt();
+        let (q, r) = (self.q as f64, self.r as f64);
+        match orientation {
+            Orientation::PointyTop => (size * (sqrt_3 * q + sqrt_3 / 2.0 * r), size * 1.5 * r),
+            Orientation::FlatTop => (size * 1.5 * q, size * (sqrt_3 / 2.0 * q + sqrt_3 * r)),
+        }
+    }
+
+    /// Hex that contains the given pixel, the inverse of `to_pixel`
+    pub fn from_pixel(x: f64, y: f64, size: f64, orientation: Orientation) -> Self {
+        let sqrt_3 = 3f64.sqrt();
+        let (q, r) = match orientation {
+            Orientation::PointyTop => ((sqrt_3 / 3.0 * x - y / 3.0) / size, 2.0 / 3.0 * y / size),
+            Orientation::FlatTop => (2.0 / 3.0 * x / size, (-x / 3.0 + sqrt_3 / 3.0 * y) / size),
+        };
+        Self::round(q, r, -q - r)
+    }
+}
+
+impl From<Axial> for Cube {
+    fn from(a: Axial) -> Self {
+        Self::new(a.q, a.r)
+    }
+}
+
+impl From<Cube> for Axial {
+    fn from(c: Cube) -> Self {
+        Self { q: c.q, r: c.r }
+    }
+}
+
+impl Axial {
+    pub fn new(q: isize, r: isize) -> Self {
+        Self { q, r }
+    }
+}
+
+impl Add for Cube {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            q: self.q + rhs.q,
+            r: self.r + rhs.r,
+            s: self.s + rhs.s,
+        }
+    }
+}
+
+impl Sub for Cube {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            q: self.q - rhs.q,
+            r: self.r - rhs.r,
+            s: self.s - rhs.s,
+        }
+    }
+}
+
+impl AddAssign for Cube {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Cube {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<isize> for Cube {
+    type Output = Self;
+    fn mul(self, rhs: isize) -> Self {
+        Self {
+            q: self.q * rhs,
+            r: self.r * rhs,
+            s: self.s * rhs,
+        }
+    }
+}
+
+impl Orientation {
+    /// The six valid directions for this orientation, counter-clockwise
+    pub fn directions(&self) -> &'static [HexDirection; 6] {
+        match self {
+            Self::PointyTop => &POINTY_TOP_DIRECTIONS,
+            Self::FlatTop => &FLAT_TOP_DIRECTIONS,
+        }
+    }
+}
+
+impl HexDirection {
+    fn index(&self, orientation: Orientation) -> Option<usize> {
+        orientation.directions().iter().position(|d| d == self)
+    }
+
+    /// Unit vector for this direction, or None if it isn't valid for the orientation
+    pub fn offset(&self, orientation: Orientation) -> Option<Cube> {
+        Some(Cube::unit(self.index(orientation)?))
+    }
+
+    /// Turn the given number of 60 degree steps counter-clockwise
+    pub fn rotate_left(&self, steps: usize, orientation: Orientation) -> Option<Self> {
+        let i = self.index(orientation)?;
+        Some(orientation.directions()[(i + steps) % 6])
+    }
+
+    /// Turn the given number of 60 degree steps clockwise
+    pub fn rotate_right(&self, steps: usize, orientation: Orientation) -> Option<Self> {
+        self.rotate_left(6 - steps % 6, orientation)
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "n" => Self::N,
+            "ne" => Self::Ne,
+            "e" => Self::E,
+            "se" => Self::Se,
+            "s" => Self::S,
+            "sw" => Self::Sw,
+            "w" => Self::W,
+            "nw" => Self::Nw,
+            _ => return Err(anyhow!("Invalid hex direction {:?}", s)),
+        })
+    }
+}
+
+impl fmt::Display for HexDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::N => "n",
+            Self::Ne => "ne",
+            Self::E => "e",
+            Self::Se => "se",
+            Self::S => "s",
+            Self::Sw => "sw",
+            Self::W => "w",
+            Self::Nw => "nw",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Parse a string of concatenated directions without separators, like `esenee`
+pub fn parse_directions(s: &str, orientation: Orientation) -> Result<Vec<HexDirection>> {
+    let valid = orientation.directions();
+    let mut out = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        // Prefer two letter directions since the first letter may be a valid direction as well
+        let dir = [2, 1]
+            .iter()
+            .filter_map(|&n| rest.get(..n))
+            .filter_map(|d| d.parse::<HexDirection>().ok())
+            .find(|d| valid.contains(d))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unexpected direction at column {} of {:?}",
+                    s.len() - rest.len() + 1,
+                    s,
+                )
+            })?;
+        rest = &rest[dir.to_string().len()..];
+        out.push(dir);
+    }
+    Ok(out)
+}
+
+/// Format directions the way `parse_directions` expects them
+pub fn format_directions(dirs: &[HexDirection]) -> String {
+    dirs.iter().map(HexDirection::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directions() -> Result<()> {
+        let dirs = parse_directions("esenee", Orientation::PointyTop)?;
+        assert_eq!(
+            dirs,
+            vec![
+                HexDirection::E,
+                HexDirection::Se,
+                HexDirection::Ne,
+                HexDirection::E,
+            ],
+        );
+        assert_eq!(format_directions(&dirs), "esenee");
+        assert!(parse_directions("n", Orientation::PointyTop).is_err());
+        assert_eq!(
+            parse_directions("nsw", Orientation::FlatTop)?,
+            vec![HexDirection::N, HexDirection::Sw],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_follow() -> Result<()> {
+        let dirs = parse_directions("nwwswee", Orientation::PointyTop)?;
+        assert_eq!(
+            Cube::origin().follow(&dirs, Orientation::PointyTop)?,
+            Cube::origin(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(Cube::distance(Cube::new(1, -3), Cube::new(-2, 1)), 4);
+        assert_eq!(Cube::new(2, 2).distance_from_origin(), 4);
+    }
+
+    #[test]
+    fn test_rings() {
+        let center = Cube::new(1, 1);
+        assert_eq!(Cube::ring(center, 0), vec![center]);
+        for radius in 1..4 {
+            let ring = Cube::ring(center, radius);
+            assert_eq!(ring.len(), 6 * radius);
+            assert!(ring.iter().all(|c| Cube::distance(*c, center) == radius));
+        }
+        assert_eq!(Cube::spiral(center, 2).len(), 19);
+    }
+
+    #[test]
+    fn test_line() {
+        let line = Cube::line(Cube::new(0, 0), Cube::new(3, -3));
+        assert_eq!(
+            line,
+            vec![
+                Cube::new(0, 0),
+                Cube::new(1, -1),
+                Cube::new(2, -2),
+                Cube::new(3, -3),
+            ],
+        );
+        let line = Cube::line(Cube::new(-2, 0), Cube::new(2, 1));
+        assert_eq!(line.len(), 6);
+        assert!(line.windows(2).all(|w| Cube::distance(w[0], w[1]) == 1));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let c = Cube::new(1, 0);
+        let center = Cube::origin();
+        let mut r = c;
+        for _ in 0..6 {
+            r = r.rotate_right(center);
+            assert_eq!(r.distance_from_origin(), 1);
+        }
+        assert_eq!(r, c);
+        assert_eq!(c.rotate_left(center).rotate_right(center), c);
+        assert_eq!(
+            HexDirection::E.rotate_left(1, Orientation::PointyTop),
+            Some(HexDirection::Ne),
+        );
+        assert_eq!(
+            HexDirection::E.rotate_right(1, Orientation::PointyTop),
+            Some(HexDirection::Se),
+        );
+    }
+
+    #[test]
+    fn test_pixel() {
+        for &orientation in &[Orientation::PointyTop, Orientation::FlatTop] {
+            for c in Cube::spiral(Cube::origin(), 3) {
+                let (x, y) = c.to_pixel(10.0, orientation);
+                assert_eq!(Cube::from_pixel(x, y, 10.0, orientation), c);
+            }
+        }
+        let (x, y) = Cube::new(1, 0).to_pixel(1.0, Orientation::PointyTop);
+        assert!((x - 3f64.sqrt()).abs() < 1e-9 && y.abs() < 1e-9);
+    }
+}
@@ -1,26 +1,149 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::reader::read_parsed_lines;
 
-/// Find num_entries in entries that sum to 2020 and return their product
-fn find_product_2020(entries: &[usize], num_entries: usize) -> Result<usize> {
-    for selection in entries.iter().combinations(num_entries) {
-        // Check if the selection sums up to 2020
-        if selection.iter().cloned().sum::<usize>() == 2020 {
-            // Return the product of the selection
-            return Ok(selection.into_iter().product());
+/// All pairs of indices whose entries sum to target
+fn two_sum(entries: &[usize], target: usize) -> Vec<Vec<usize>> {
+    let mut seen: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut solutions = Vec::new();
+    for (j, entry) in entries.iter().enumerate() {
+        if let Some(is) = target.checked_sub(*entry).and_then(|rest| seen.get(&rest)) {
+            solutions.extend(is.iter().map(|i| vec![*i, j]));
         }
+        seen.entry(*entry).or_default().push(j);
     }
-    Err(anyhow!("No matching entries"))
+    solutions
+}
+
+/// All triples of indices whose entries sum to target, using two pointers over the sorted entries
+fn three_sum(entries: &[usize], target: usize) -> Vec<Vec<usize>> {
+    let mut order = (0..entries.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| entries[*i]);
+    let value = |i: usize| entries[order[i]];
+
+    let mut solutions = Vec::new();
+    for first in 0..order.len() {
+        let rest = match target.checked_sub(value(first)) {
+            Some(rest) => rest,
+            None => break,
+        };
+        let (mut lo, mut hi) = (first + 1, order.len().saturating_sub(1));
+        while lo < hi {
+            let sum = value(lo) + value(hi);
+            if sum < rest {
+                lo += 1;
+            } else if sum > rest {
+                hi -= 1;
+            } else if value(lo) == value(hi) {
+                // Every pair in between has the same value
+                for (a, b) in (lo..=hi).tuple_combinations() {
+                    solutions.push(vec![order[first], order[a], order[b]]);
+                }
+                break;
+            } else {
+                // Pair up the runs of equal values on both sides
+                let lo_end = (lo..=hi).find(|i| value(*i) != value(lo)).unwrap();
+                let hi_start = (lo_end..=hi).find(|i| value(*i) == value(hi)).unwrap();
+                for a in lo..lo_end {
+                    for b in hi_start..=hi {
+                        solutions.push(vec![order[first], order[a], order[b]]);
+                    }
+                }
+                lo = lo_end;
+                hi = hi_start - 1;
+            }
+        }
+    }
+    solutions
+}
+
+/// All sets of k indices whose entries sum to target, by meeting in the middle. Every set is
+/// split into its lower and upper half of indices, so each is only found once.
+fn meet_in_the_middle(entries: &[usize], target: usize, k: usize) -> Vec<Vec<usize>> {
+    let (k_low, k_high) = (k / 2, k - k / 2);
+    let mut lower_halves: HashMap<usize, Vec<Vec<usize>>> = HashMap::new();
+    for half in (0..entries.len()).combinations(k_low) {
+        let sum = half.iter().map(|i| entries[*i]).sum();
+        lower_halves.entry(sum).or_default().push(half);
+    }
+
+    let mut solutions = Vec::new();
+    for half in (0..entries.len()).combinations(k_high) {
+        let sum = half.iter().map(|i| entries[*i]).sum::<usize>();
+        let lows = match target
+            .checked_sub(sum)
+            .and_then(|rest| lower_halves.get(&rest))
+        {
+            Some(lows) => lows,
+            None => continue,
+        };
+        for low in lows.iter().filter(|low| low.last() < half.first()) {
+            solutions.push(low.iter().chain(half.iter()).copied().collect());
+        }
+    }
+    solutions
+}
+
+/// Every set of k entries that sum to target, as sorted indices into entries. The solutions are
+/// sorted too.
+pub fn k_sum(entries: &[usize], target: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut solutions = match k {
+        0 => Vec::new(),
+        1 => (0..entries.len())
+            .filter(|i| entries[*i] == target)
+            .map(|i| vec![i])
+            .collect(),
+        2 => two_sum(entries, target),
+        3 => three_sum(entries, target),
+        _ => meet_in_the_middle(entries, target, k),
+    };
+    for s in solutions.iter_mut() {
+        s.sort_unstable();
+    }
+    solutions.sort();
+    solutions
+}
+
+/// Find k entries that sum to target and return their product
+fn find_product(entries: &[usize], target: usize, k: usize) -> Result<usize> {
+    k_sum(entries, target, k)
+        .first()
+        .map(|indices| indices.iter().map(|i| entries[*i]).product())
+        .ok_or_else(|| anyhow!("No {} entries sum to {}", k, target))
+}
+
+/// List every solution for the target and k given as options
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let (target, k) = match options {
+        [target, k] => (
+            target.parse().context("Invalid target")?,
+            k.parse().context("Invalid k")?,
+        ),
+        _ => return Err(anyhow!("Expected options: <target> <k>")),
+    };
+    let entries = read_parsed_lines(path)?.collect::<Result<Vec<usize>>>()?;
+    let solutions = k_sum(&entries, target, k);
+    let mut output = format!("{} solutions", solutions.len());
+    for indices in solutions {
+        let values = indices.iter().map(|i| entries[*i]).collect::<Vec<_>>();
+        output += &format!(
+            "\nLines {}: {} (product {})",
+            indices.iter().map(|i| i + 1).join(", "),
+            values.iter().join(" + "),
+            values.iter().product::<usize>(),
+        );
+    }
+    Ok(output)
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
     let entries = read_parsed_lines(path)?.collect::<Result<Vec<usize>>>()?;
     Ok((
-        find_product_2020(&entries, 2)?,
-        Some(find_product_2020(&entries, 3)?),
+        find_product(&entries, 2020, 2)?,
+        Some(find_product(&entries, 2020, 3)?),
     ))
 }
 
@@ -30,13 +153,38 @@ mod tests {
 
     #[test]
     fn test_invalid_input() -> Result<()> {
-        assert!(find_product_2020(&[1010], 2).is_err());
+        assert!(find_product(&[1010], 2020, 2).is_err());
         Ok(())
     }
 
     #[test]
     fn test_tricky_input() -> Result<()> {
-        assert_eq!(find_product_2020(&[1010, 2010, 10], 2)?, 20100);
+        assert_eq!(find_product(&[1010, 2010, 10], 2020, 2)?, 20100);
         Ok(())
     }
+
+    #[test]
+    fn test_k_sum() {
+        let entries = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(k_sum(&entries, 2020, 2), vec![vec![0, 3]]);
+        assert_eq!(k_sum(&entries, 2020, 3), vec![vec![1, 2, 4]]);
+
+        // Every approach must agree with trying all combinations, including duplicates
+        let entries = [1, 2, 2, 3, 3, 3, 4, 5, 0, 6];
+        for k in 1..=5 {
+            for target in 0..=20 {
+                let expected = (0..entries.len())
+                    .combinations(k)
+                    .filter(|c| c.iter().map(|i| entries[*i]).sum::<usize>() == target)
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    k_sum(&entries, target, k),
+                    expected,
+                    "k={} target={}",
+                    k,
+                    target
+                );
+            }
+        }
+    }
 }
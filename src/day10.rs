@@ -30,7 +30,6 @@ fn solve(mut input: Vec<usize>) -> (usize, Option<u128>) {
         // Check the previous 3 adapter jolt values for the number of valid paths.
         // The sum of those paths are the number of valid paths to this adapter
         let num_paths = (1..=3)
-            .into_iter()
             .filter_map(|d| v.checked_sub(d).and_then(|p| valid_paths.get(&p)))
             .sum();
         valid_paths.insert(*v, num_paths);
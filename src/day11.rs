@@ -1,8 +1,10 @@
 use anyhow::{anyhow, Result};
+use std::convert::TryFrom;
 use std::path::Path;
 
-use crate::coord::Coord;
-use crate::reader::read_lines;
+use crate::automaton::{Automaton, Cell, Moore, Neighborhood};
+use crate::coord::{Coord, Direction};
+use crate::reader::{Grid, ToChar};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
@@ -11,29 +13,12 @@ enum Tile {
     Occupied,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Layout {
-    width: usize,
-    height: usize,
-    tiles: Vec<Tile>,
-}
-
-impl Layout {
-    fn coord_to_index(&self, c: &Coord) -> Option<usize> {
-        if c.x < 0 || c.y < 0 || c.x >= self.width as isize || c.y >= self.height as isize {
-            return None;
-        }
-        Some(c.y as usize * self.width + c.x as usize % self.width)
-    }
+type Layout = Grid<Tile>;
 
-    fn get(&self, c: &Coord) -> Option<Tile> {
-        let i = self.coord_to_index(c)?;
-        self.tiles.get(i).copied()
-    }
-}
+impl TryFrom<char> for Tile {
+    type Error = anyhow::Error;
 
-impl Tile {
-    fn from_char(c: char) -> Result<Self> {
+    fn try_from(c: char) -> Result<Self> {
         Ok(match c {
             '.' => Self::Floor,
             'L' => Self::Empty,
@@ -43,132 +28,121 @@ impl Tile {
     }
 }
 
-fn run_until_steady_state<F>(
-    mut layout: Layout,
-    max_neighbors: usize,
-    num_neighbors: F,
-) -> Result<usize>
-where
-    F: Fn(&Layout, &Coord) -> usize,
-{
-    let mut new_tiles = Vec::with_capacity(layout.tiles.len());
-    loop {
-        for y in 0..layout.height {
-            for x in 0..layout.width {
-                let c = Coord::new(x as isize, y as isize);
-                let tile = layout
-                    .get(&c)
-                    .ok_or_else(|| anyhow!("Coordinate {:?} not in layout", c))?;
-                let num_neighbors = num_neighbors(&layout, &c);
-                new_tiles.push(match tile {
-                    Tile::Floor => Tile::Floor,
-                    Tile::Empty => {
-                        if num_neighbors == 0 {
-                            Tile::Occupied
-                        } else {
-                            Tile::Empty
-                        }
-                    }
-                    Tile::Occupied => {
-                        if num_neighbors >= max_neighbors {
-                            Tile::Empty
-                        } else {
-                            Tile::Occupied
-                        }
-                    }
-                });
+impl ToChar for Tile {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Floor => '.',
+            Self::Empty => 'L',
+            Self::Occupied => '#',
+        }
+    }
+}
+
+impl Cell for Tile {
+    fn is_alive(&self) -> bool {
+        *self == Self::Occupied
+    }
+}
+
+fn seat_rule(max_neighbors: usize) -> impl Fn(&Tile, usize) -> Tile {
+    move |tile, num_neighbors| match tile {
+        Tile::Floor => Tile::Floor,
+        Tile::Empty => {
+            if num_neighbors == 0 {
+                Tile::Occupied
+            } else {
+                Tile::Empty
             }
         }
-        if new_tiles == layout.tiles {
-            break;
+        Tile::Occupied => {
+            if num_neighbors >= max_neighbors {
+                Tile::Empty
+            } else {
+                Tile::Occupied
+            }
         }
-        std::mem::swap(&mut layout.tiles, &mut new_tiles);
-        new_tiles.clear();
     }
-    Ok(layout
-        .tiles
-        .into_iter()
-        .filter(|t| *t == Tile::Occupied)
-        .count())
 }
 
-fn part_a(layout: Layout) -> Result<usize> {
-    run_until_steady_state(layout, 4, |layout, coord| {
-        coord
-            .iter_all_neighbors()
-            .filter(|n| layout.get(&n) == Some(Tile::Occupied))
-            .count()
-    })
+fn num_occupied_when_stable<N>(layout: Layout, max_neighbors: usize, neighborhood: N) -> usize
+where
+    N: Neighborhood<Coord>,
+{
+    let mut automaton = Automaton::new(layout, neighborhood, seat_rule(max_neighbors));
+    automaton.run_until_stable();
+    automaton.population()
 }
 
-fn part_b(layout: Layout) -> Result<usize> {
-    run_until_steady_state(layout, 5, |layout, coord| {
-        Coord::origin()
-            .iter_all_neighbors()
-            .filter(|d| {
-                for i in 1.. {
-                    let ray = Coord::new(coord.x + d.x * i, coord.y + d.y * i);
-                    match layout.get(&ray) {
-                        Some(Tile::Floor) => {}
-                        Some(Tile::Occupied) => {
-                            return true;
-                        }
-                        Some(Tile::Empty) | None => {
-                            return false;
-                        }
-                    };
-                }
-                unreachable!();
-            })
-            .count()
-    })
+fn part_a(layout: Layout) -> usize {
+    num_occupied_when_stable(layout, 4, Moore)
+}
+
+fn part_b(layout: Layout) -> usize {
+    // Seats never move, so the first seat visible in each direction only has to be found once
+    let visibility = layout.visibility(Direction::all(1), |_, t| *t != Tile::Floor);
+    num_occupied_when_stable(layout, 5, |c: &Coord| visibility.get(c).iter().copied())
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let mut tiles = Vec::new();
-    let mut height = 0;
-    for line in read_lines(path)? {
-        for c in line?.chars() {
-            tiles.push(Tile::from_char(c)?);
-        }
-        height += 1;
-    }
-    let layout = Layout {
-        width: tiles.len() / height,
-        height,
-        tiles,
-    };
-    Ok((part_a(layout.clone())?, Some(part_b(layout)?)))
+    let layout = Layout::from_path(path)?;
+    Ok((part_a(layout.clone()), Some(part_b(layout))))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert_grid_eq;
 
     #[test]
     fn test_example() -> Result<()> {
-        let layout = Layout {
-            width: 10,
-            height: 10,
-            tiles: vec![
-                "L.LL.LL.LL",
-                "LLLLLLL.LL",
+        let layout = Layout::from_lines([
+            "L.LL.LL.LL",
+            "LLLLLLL.LL",
+            "L.L.L..L..",
+            "LLLL.LL.LL",
+            "L.LL.LL.LL",
+            "L.LLLLL.LL",
+            "..L.L.....",
+            "LLLLLLLLLL",
+            "L.LLLLLL.L",
+            "L.LLLLL.LL",
+        ])?;
+        let mut automaton = Automaton::new(layout.clone(), Moore, seat_rule(4));
+        automaton.step();
+        assert_grid_eq!(
+            automaton.cells,
+            Layout::from_lines([
+                "#.##.##.##",
+                "#######.##",
+                "#.#.#..#..",
+                "####.##.##",
+                "#.##.##.##",
+                "#.#####.##",
+                "..#.#.....",
+                "##########",
+                "#.######.#",
+                "#.#####.##",
+            ])?,
+        );
+        automaton.step();
+        assert_grid_eq!(
+            automaton.cells,
+            Layout::from_lines([
+                "#.LL.L#.##",
+                "#LLLLLL.L#",
                 "L.L.L..L..",
-                "LLLL.LL.LL",
-                "L.LL.LL.LL",
-                "L.LLLLL.LL",
+                "#LLL.LL.L#",
+                "#.LL.LL.LL",
+                "#.LLLL#.##",
                 "..L.L.....",
-                "LLLLLLLLLL",
-                "L.LLLLLL.L",
-                "L.LLLLL.LL",
-            ]
-            .into_iter()
-            .map(|l| l.chars().map(|c| Tile::from_char(c).unwrap()))
-            .flatten()
-            .collect(),
-        };
-        assert_eq!(part_a(layout.clone())?, 37);
-        assert_eq!(part_b(layout)?, 26);
+                "#LLLLLLLL#",
+                "#.LLLLLL.L",
+                "#.#LLLL.##",
+            ])?,
+        );
+
+        assert_eq!(part_a(layout.clone()), 37);
+        assert_eq!(part_b(layout), 26);
         Ok(())
     }
 }
@@ -16,16 +16,12 @@ enum Action {
     Forward(usize),
 }
 
-fn num_turns(degrees: usize) -> Result<usize> {
-    match degrees {
-        90 => Ok(1),
-        180 => Ok(2),
-        270 => Ok(3),
-        _ => Err(anyhow!(
-            "Degrees must be either 90, 180 or 270, got {}",
-            degrees
-        )),
+fn parse_degrees(s: &str) -> Result<usize> {
+    let degrees = s.parse::<usize>()?;
+    if degrees % 90 != 0 {
+        return Err(anyhow!("Degrees must be a multiple of 90, got {}", degrees));
     }
+    Ok(degrees)
 }
 
 impl FromStr for Action {
@@ -41,8 +37,8 @@ impl FromStr for Action {
                 'S' => Action::South(s[1..].parse()?),
                 'E' => Action::East(s[1..].parse()?),
                 'W' => Action::West(s[1..].parse()?),
-                'L' => Action::Left(num_turns(s[1..].parse()?)?),
-                'R' => Action::Right(num_turns(s[1..].parse()?)?),
+                'L' => Action::Left(parse_degrees(&s[1..])?),
+                'R' => Action::Right(parse_degrees(&s[1..])?),
                 'F' => Action::Forward(s[1..].parse()?),
                 c => return Err(anyhow!("Got invalid action ({})", c)),
             },
@@ -67,22 +63,18 @@ fn part_a(actions: &[Action]) -> Result<usize> {
             Action::West(n) => {
                 ship.offset_mut(Direction::Left(*n));
             }
-            Action::Left(n) => {
-                for _ in 0..*n {
-                    dir = dir.turn_left();
-                }
+            Action::Left(degrees) => {
+                dir = dir.rotate(-(*degrees as isize))?;
             }
-            Action::Right(n) => {
-                for _ in 0..*n {
-                    dir = dir.turn_right();
-                }
+            Action::Right(degrees) => {
+                dir = dir.rotate(*degrees as isize)?;
             }
             Action::Forward(n) => {
                 ship.offset_mut(dir.resize(*n));
             }
         }
     }
-    Ok(ship.x.abs() as usize + ship.y.abs() as usize)
+    Ok(ship.x.unsigned_abs() + ship.y.unsigned_abs())
 }
 
 fn part_b(actions: &[Action]) -> Result<usize> {
@@ -102,15 +94,11 @@ fn part_b(actions: &[Action]) -> Result<usize> {
             Action::West(n) => {
                 waypoint.offset_mut(Direction::Left(*n));
             }
-            Action::Left(n) => {
-                for _ in 0..*n {
-                    waypoint = Coord::new(waypoint.y, -waypoint.x);
-                }
+            Action::Left(degrees) => {
+                waypoint = waypoint.rotate(-(*degrees as isize))?;
             }
-            Action::Right(n) => {
-                for _ in 0..*n {
-                    waypoint = Coord::new(-waypoint.y, waypoint.x);
-                }
+            Action::Right(degrees) => {
+                waypoint = waypoint.rotate(*degrees as isize)?;
             }
             Action::Forward(n) => {
                 for _ in 0..*n {
@@ -119,7 +107,7 @@ fn part_b(actions: &[Action]) -> Result<usize> {
             }
         }
     }
-    Ok(ship.x.abs() as usize + ship.y.abs() as usize)
+    Ok(ship.x.unsigned_abs() + ship.y.unsigned_abs())
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
@@ -139,6 +127,8 @@ mod tests {
             .collect::<Result<Vec<Action>>>()?;
         assert_eq!(part_a(&actions)?, 25);
         assert_eq!(part_b(&actions)?, 286);
+        assert!("L45".parse::<Action>().is_err());
+        assert!("R-90".parse::<Action>().is_err());
         Ok(())
     }
 }
@@ -1,14 +1,13 @@
 use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
-use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
 
-use crate::reader::read_parsed_lines;
+use crate::reader::{read_parsed_lines, Template};
 
-static ACTION_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(mask|mem)(?:\[(\d+)\])? = ([01X]+|\d+)$").unwrap());
+static MASK_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::new("mask = {}").unwrap());
+static MEMSET_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::new("mem[{}] = {}").unwrap());
 
 #[derive(Debug)]
 enum Action {
@@ -20,23 +19,22 @@ impl FromStr for Action {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let c = ACTION_RE
-            .captures(s)
-            .ok_or_else(|| anyhow!("Invalid action {:?}", s))?;
-        Ok(match &c[1] {
-            "mask" => Action::Mask(
-                c[3].chars()
+        if s.starts_with("mask") {
+            let (mask,): (String,) = MASK_TEMPLATE.scan(s)?;
+            Ok(Action::Mask(
+                mask.chars()
                     .map(|c| match c {
-                        '0' => Some(false),
-                        '1' => Some(true),
-                        'X' => None,
-                        _ => unreachable!(),
+                        '0' => Ok(Some(false)),
+                        '1' => Ok(Some(true)),
+                        'X' => Ok(None),
+                        c => Err(anyhow!("Invalid mask character {:?}", c)),
                     })
-                    .collect(),
-            ),
-            "mem" => Action::Memset(c[2].parse()?, c[3].parse()?),
-            _ => unreachable!(),
-        })
+                    .collect::<Result<_>>()?,
+            ))
+        } else {
+            let (address, value) = MEMSET_TEMPLATE.scan(s)?;
+            Ok(Action::Memset(address, value))
+        }
     }
 }
 
@@ -1,9 +1,10 @@
 use anyhow::{anyhow, Result};
-use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::ops::RangeInclusive;
 use std::path::Path;
 
+use crate::reader::{read_typed_sections, Section, Template};
+
 /// Return the set of valid rules for this value
 fn valid_rules(
     rules: &HashMap<String, (RangeInclusive<usize>, RangeInclusive<usize>)>,
@@ -26,29 +27,23 @@ fn parse_ticket(line: &str) -> Result<Vec<usize>> {
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
     // Read input and split into segments (rules, my ticket and nearby_tickets)
-    let input = std::fs::read_to_string(path)?;
-    let mut sections = input.trim_end().split("\n\n");
-    let rules_str = sections.next().unwrap();
-    let my_ticket_str = sections.next().unwrap();
-    let nearby_tickets_str = sections.next().unwrap();
+    let (rules_section, my_ticket_section, nearby_tickets_section): (Section, Section, Section) =
+        read_typed_sections(path)?;
 
-    let my_ticket = parse_ticket(my_ticket_str.lines().last().unwrap())?;
-    let nearby_tickets = nearby_tickets_str
-        .lines()
-        .skip(1)
-        .map(parse_ticket)
-        .collect::<Result<Vec<_>>>()?;
+    let my_ticket = my_ticket_section
+        .parse_with(|s| parse_ticket(s.lines.last().ok_or_else(|| anyhow!("No ticket given"))?))?;
+    let nearby_tickets = nearby_tickets_section.parse_with(|s| {
+        s.body()
+            .iter()
+            .map(|l| parse_ticket(l))
+            .collect::<Result<Vec<_>>>()
+    })?;
 
     let mut rules = HashMap::new();
-    let rule_re = Regex::new(r"^([^:]+): (\d+)-(\d+) or (\d+)-(\d+)$")?;
-    for rule_str in rules_str.lines() {
-        let c = rule_re
-            .captures(rule_str)
-            .ok_or_else(|| anyhow!("Invalid rule {:?}", rule_str))?;
-        rules.insert(
-            c[1].to_owned(),
-            (c[2].parse()?..=c[3].parse()?, c[4].parse()?..=c[5].parse()?),
-        );
+    let rule_template = Template::new("{}: {}-{} or {}-{}")?;
+    for rule_str in rules_section.lines.iter() {
+        let (field, a, b, c, d): (String, _, _, _, _) = rule_template.scan(rule_str)?;
+        rules.insert(field, (a..=b, c..=d));
     }
 
     // Track each position with a set containing all possible rules that can govern it
@@ -56,9 +51,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
         .keys()
         .map(|field| field.as_str())
         .collect::<HashSet<_>>();
-    let mut possible_rules_by_slot = std::iter::repeat(all_fields.clone())
-        .take(all_fields.len())
-        .collect::<Vec<_>>();
+    let mut possible_rules_by_slot = vec![all_fields.clone(); all_fields.len()];
 
     // Count all invalid tickets. If the tickets are valid, use that to reduce the set
     // of possible rules for that position
@@ -32,7 +32,7 @@ impl Expr {
 
 fn num(input: &str) -> IResult<&str, Expr> {
     map_res(
-        take_while(|c: char| c.is_digit(10)),
+        take_while(|c: char| c.is_ascii_digit()),
         |s: &str| -> Result<Expr> { Ok(Expr::Scalar(s.parse()?)) },
     )(input)
 }
@@ -81,7 +81,7 @@ mod part_b {
         })(input)
     }
 
-    pub fn expr<'a>(input: &'a str) -> IResult<&'a str, Expr> {
+    pub fn expr(input: &str) -> IResult<&str, Expr> {
         let (input, init) = alt((terms, subexpr, num))(input)?;
         fold_many0(
             preceded(tag(" * "), alt((terms, subexpr, num))),
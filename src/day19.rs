@@ -4,7 +4,7 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 
-use crate::reader::split_once;
+use crate::reader::{read_typed_sections, split_once, Section};
 
 #[derive(Debug)]
 enum Rule {
@@ -58,11 +58,11 @@ impl FromStr for Rule {
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let input_str = std::fs::read_to_string(path)?;
-    let (rules_str, data_str) = split_once(&input_str, "\n\n");
+    let (rules_section, messages_section): (Section, Section) = read_typed_sections(path)?;
 
-    let rules = rules_str
-        .lines()
+    let rules = rules_section
+        .lines
+        .iter()
         .map(|l| {
             if let (n, Some(rule_str)) = split_once(l, ": ") {
                 Ok((n.parse::<usize>()?, Rule::from_str(rule_str)?))
@@ -88,11 +88,10 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
 
     // Split the messages into two sets. The first contains all messages that are valid in part A.
     // These messages are still valid in part B.
-    let (part_a_msgs, rest): (Vec<_>, Vec<_>) = data_str
-        .ok_or_else(|| anyhow!("No messages found in input"))?
-        .lines()
-        .into_iter()
-        .partition(|m| rule_0.contains(m.to_owned()));
+    let (part_a_msgs, rest): (Vec<_>, Vec<_>) = messages_section
+        .lines
+        .iter()
+        .partition(|m| rule_0.contains(m.as_str()));
 
     // For part B we have the following three rules:
     //
@@ -1,47 +1,303 @@
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::fmt;
+use std::ops::RangeInclusive;
 use std::path::Path;
 use std::str::FromStr;
 
-use crate::reader::read_parsed_lines;
+use crate::reader::{read_parsed_lines, Input, Template};
 
-// Create the row parsing regex once only to save some performance
-static ROW_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+)-(\d+)\s+([a-z]):\s+(\S+)$").unwrap());
+// Compile the row template once only to save some performance
+static ROW_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::new("{}-{} {}: {}").unwrap());
 
 #[derive(Debug)]
-struct PasswordEntry {
-    first: usize,
-    second: usize,
-    letter: char,
-    password: String,
+pub struct PasswordEntry {
+    pub first: usize,
+    pub second: usize,
+    pub letter: char,
+    pub password: String,
 }
 
-impl PasswordEntry {
-    fn has_valid_frequency(&self) -> bool {
-        let num_matches = self.password.chars().filter(|c| *c == self.letter).count();
-        (self.first..=self.second).contains(&num_matches)
+/// A rule that passwords have to follow
+pub trait PasswordPolicy {
+    /// Why the entry breaks the policy, or None if it follows it
+    fn violation(&self, entry: &PasswordEntry) -> Option<String>;
+
+    fn is_valid(&self, entry: &PasswordEntry) -> bool {
+        self.violation(entry).is_none()
+    }
+}
+
+/// The entry's letter must appear between first and second times
+pub struct Frequency;
+
+/// The entry's letter must be at exactly one of the first and second positions, starting from 1
+pub struct Positions;
+
+/// The password must contain a match of the regex. Anchor it with `^` and `$` to match the
+/// whole password.
+pub struct Pattern(pub Regex);
+
+/// The number of characters of a class must be in a range
+pub struct ClassCount {
+    pub class: CharClass,
+    pub range: RangeInclusive<usize>,
+}
+
+/// None of the substrings may appear in the password
+pub struct Forbidden(pub Vec<String>);
+
+/// The number of characters in the password must be in a range
+pub struct Length(pub RangeInclusive<usize>);
+
+/// Kinds of characters, using the Unicode definitions so that non-ASCII passwords are counted
+/// too
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl PasswordPolicy for Frequency {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let num_matches = entry
+            .password
+            .chars()
+            .filter(|c| *c == entry.letter)
+            .count();
+        if (entry.first..=entry.second).contains(&num_matches) {
+            None
+        } else {
+            Some(format!(
+                "{:?} appears {} times, expected {} to {}",
+                entry.letter, num_matches, entry.first, entry.second,
+            ))
+        }
+    }
+}
+
+impl PasswordPolicy for Positions {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        // Positions past the end of the password never match
+        let is_letter = |position: usize| {
+            position
+                .checked_sub(1)
+                .and_then(|i| entry.password.chars().nth(i))
+                == Some(entry.letter)
+        };
+        match (is_letter(entry.first), is_letter(entry.second)) {
+            (true, false) | (false, true) => None,
+            (true, true) => Some(format!(
+                "{:?} is at both positions {} and {}",
+                entry.letter, entry.first, entry.second,
+            )),
+            (false, false) => Some(format!(
+                "{:?} is at neither position {} nor {}",
+                entry.letter, entry.first, entry.second,
+            )),
+        }
+    }
+}
+
+impl PasswordPolicy for Pattern {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        if self.0.is_match(&entry.password) {
+            None
+        } else {
+            Some(format!("doesn't match /{}/", self.0))
+        }
+    }
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Lowercase => c.is_lowercase(),
+            Self::Uppercase => c.is_uppercase(),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Symbol => !c.is_alphabetic() && !c.is_ascii_digit() && !c.is_whitespace(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Lowercase => "lowercase letters",
+            Self::Uppercase => "uppercase letters",
+            Self::Digit => "digits",
+            Self::Symbol => "symbols",
+        }
+    }
+}
+
+impl PasswordPolicy for ClassCount {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let count = entry
+            .password
+            .chars()
+            .filter(|c| self.class.matches(*c))
+            .count();
+        if self.range.contains(&count) {
+            None
+        } else {
+            Some(format!(
+                "has {} {}, expected {}",
+                count,
+                self.class.name(),
+                format_range(&self.range),
+            ))
+        }
+    }
+}
+
+impl PasswordPolicy for Forbidden {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        self.0
+            .iter()
+            .find(|s| entry.password.contains(s.as_str()))
+            .map(|s| format!("contains {:?}", s))
+    }
+}
+
+impl PasswordPolicy for Length {
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let length = entry.password.chars().count();
+        if self.0.contains(&length) {
+            None
+        } else {
+            Some(format!(
+                "is {} characters long, expected {}",
+                length,
+                format_range(&self.0),
+            ))
+        }
+    }
+}
+
+fn format_range(range: &RangeInclusive<usize>) -> String {
+    if *range.end() == usize::MAX {
+        format!("at least {}", range.start())
+    } else {
+        format!("{} to {}", range.start(), range.end())
+    }
+}
+
+/// Parse ranges like "3-5", or "3-" without an upper limit
+fn parse_range(s: &str) -> Result<RangeInclusive<usize>> {
+    let (min, max) = s
+        .split_once('-')
+        .ok_or_else(|| anyhow!("Expected a range like 3-5, got {:?}", s))?;
+    let min = min
+        .parse()
+        .with_context(|| format!("Invalid range {:?}", s))?;
+    let max = if max.is_empty() {
+        usize::MAX
+    } else {
+        max.parse()
+            .with_context(|| format!("Invalid range {:?}", s))?
+    };
+    Ok(min..=max)
+}
+
+/// Parse a policy given as `frequency`, `positions`, `regex:<pattern>`, `length:<range>`,
+/// `forbid:<a>,<b>` or `<class>:<range>` where class is lowercase, uppercase, digits or symbols
+pub fn parse_policy(s: &str) -> Result<Box<dyn PasswordPolicy>> {
+    let (name, arg) = match s.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (s, None),
+    };
+    let class = |class| -> Result<Box<dyn PasswordPolicy>> {
+        let range = parse_range(arg.ok_or_else(|| anyhow!("{} needs a range", name))?)?;
+        Ok(Box::new(ClassCount { class, range }))
+    };
+    Ok(match (name, arg) {
+        ("frequency", None) => Box::new(Frequency),
+        ("positions", None) => Box::new(Positions),
+        ("regex", Some(pattern)) => Box::new(Pattern(Regex::new(pattern)?)),
+        ("length", Some(range)) => Box::new(Length(parse_range(range)?)),
+        ("forbid", Some(words)) => {
+            Box::new(Forbidden(words.split(',').map(|w| w.to_owned()).collect()))
+        }
+        ("lowercase", _) => class(CharClass::Lowercase)?,
+        ("uppercase", _) => class(CharClass::Uppercase)?,
+        ("digits", _) => class(CharClass::Digit)?,
+        ("symbols", _) => class(CharClass::Symbol)?,
+        _ => return Err(anyhow!("Unknown policy {:?}", s)),
+    })
+}
+
+/// Every line that breaks one of the policies or can't be parsed, with its line number and the
+/// reason
+fn report(input: &Input, policies: &[Box<dyn PasswordPolicy>]) -> Vec<String> {
+    let mut failures = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let entry = match line.parse::<PasswordEntry>() {
+            Ok(entry) => entry,
+            Err(error) => {
+                failures.push(format!("Line {}: {}: {:#}", i + 1, line, error));
+                continue;
+            }
+        };
+        for reason in policies.iter().filter_map(|p| p.violation(&entry)) {
+            failures.push(format!("Line {}: {}: {}", i + 1, entry, reason));
+        }
     }
+    failures
+}
+
+/// List every line that breaks the given policies, by default the ones from both parts
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let policies = match options {
+        [mode] if mode == "report" => vec![parse_policy("frequency")?, parse_policy("positions")?],
+        [mode, policies @ ..] if mode == "report" => policies
+            .iter()
+            .map(|p| parse_policy(p))
+            .collect::<Result<Vec<_>>>()?,
+        _ => return Err(anyhow!("Expected options: report [policy...]")),
+    };
+    let input = Input::from_path(path)?;
+    let failures = report(&input, &policies);
+    Ok(format!(
+        "{} violations in {} lines\n{}",
+        failures.len(),
+        input.lines().count(),
+        failures.join("\n"),
+    ))
+}
 
-    fn has_valid_positions(&self) -> bool {
-        let first = self.password.chars().nth(self.first - 1).unwrap();
-        let second = self.password.chars().nth(self.second - 1).unwrap();
-        (first == self.letter) ^ (second == self.letter)
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.first, self.second, self.letter, self.password
+        )
     }
 }
 
 impl FromStr for PasswordEntry {
     type Err = Error;
 
+    /// Fields are separated by single spaces, the letter is lowercase ASCII and the password is a
+    /// single word
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let captures = ROW_RE
-            .captures(s)
-            .ok_or_else(|| anyhow!("String doesn't match policy with password"))?;
+        let (first, second, letter, password): (_, _, char, String) = ROW_TEMPLATE.scan(s)?;
+        if !letter.is_ascii_lowercase() {
+            return Err(anyhow!("Expected a lowercase letter, found {:?}", letter));
+        }
+        if password.is_empty() || password.contains(char::is_whitespace) {
+            return Err(anyhow!(
+                "Expected a password without spaces, found {:?}",
+                password
+            ));
+        }
         Ok(Self {
-            first: captures[1].parse()?,
-            second: captures[2].parse()?,
-            letter: captures[3].chars().next().unwrap(),
-            password: captures[4].to_owned(),
+            first,
+            second,
+            letter,
+            password,
         })
     }
 }
@@ -49,8 +305,8 @@ impl FromStr for PasswordEntry {
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
     let entries = read_parsed_lines(path)?.collect::<Result<Vec<PasswordEntry>>>()?;
     Ok((
-        entries.iter().filter(|e| e.has_valid_frequency()).count(),
-        Some(entries.iter().filter(|e| e.has_valid_positions()).count()),
+        entries.iter().filter(|e| Frequency.is_valid(e)).count(),
+        Some(entries.iter().filter(|e| Positions.is_valid(e)).count()),
     ))
 }
 
@@ -61,16 +317,86 @@ mod tests {
     #[test]
     fn test_validation() -> Result<()> {
         let p1 = PasswordEntry::from_str("1-3 a: abcde")?;
-        assert!(p1.has_valid_frequency());
-        assert!(p1.has_valid_positions());
+        assert!(Frequency.is_valid(&p1));
+        assert!(Positions.is_valid(&p1));
 
         let p2 = PasswordEntry::from_str("1-3 b: cdefg")?;
-        assert!(!p2.has_valid_frequency());
-        assert!(!p2.has_valid_positions());
+        assert!(!Frequency.is_valid(&p2));
+        assert!(!Positions.is_valid(&p2));
 
         let p3 = PasswordEntry::from_str("2-9 c: ccccccccc")?;
-        assert!(p3.has_valid_frequency());
-        assert!(!p3.has_valid_positions());
+        assert!(Frequency.is_valid(&p3));
+        assert!(!Positions.is_valid(&p3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(PasswordEntry::from_str("1-3 ab: abcde").is_err());
+        assert!(PasswordEntry::from_str("1-3 A: abcde").is_err());
+        assert!(PasswordEntry::from_str("1-3 a: abc de").is_err());
+        assert!(PasswordEntry::from_str("1-3 a:  abcde").is_err());
+        assert!(PasswordEntry::from_str("1-3 a: ").is_err());
+        assert!(PasswordEntry::from_str("1-3  a: abcde").is_err());
+    }
+
+    #[test]
+    fn test_positions_out_of_range() -> Result<()> {
+        assert!(Positions.is_valid(&PasswordEntry::from_str("2-9 c: ac")?));
+        assert!(!Positions.is_valid(&PasswordEntry::from_str("5-9 c: ccc")?));
+        assert!(!Positions.is_valid(&PasswordEntry::from_str("0-9 c: ccc")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_policies() -> Result<()> {
+        let entry = PasswordEntry::from_str("1-3 a: Abc1!password")?;
+        assert!(parse_policy("regex:^[A-Z]")?.is_valid(&entry));
+        assert!(parse_policy("regex:pass")?.is_valid(&entry));
+        assert!(!parse_policy("regex:^pass$")?.is_valid(&entry));
+        assert!(parse_policy("length:8-")?.is_valid(&entry));
+        assert!(parse_policy("digits:1-1")?.is_valid(&entry));
+        assert!(parse_policy("lowercase:2-2")?.is_valid(&PasswordEntry::from_str("1-3 a: éß")?));
+        assert!(parse_policy("symbols:1-")?.is_valid(&entry));
+        let fractions = PasswordEntry::from_str("1-3 a: ½²Ⅻ7")?;
+        assert!(parse_policy("digits:1-1")?.is_valid(&fractions));
+        assert!(parse_policy("symbols:2-2")?.is_valid(&fractions));
+        assert_eq!(
+            parse_policy("uppercase:2-")?.violation(&entry),
+            Some("has 1 uppercase letters, expected at least 2".to_owned()),
+        );
+        assert_eq!(
+            parse_policy("forbid:123,pass")?.violation(&entry),
+            Some("contains \"pass\"".to_owned()),
+        );
+        assert_eq!(
+            parse_policy("length:1-5")?.violation(&entry),
+            Some("is 13 characters long, expected 1 to 5".to_owned()),
+        );
+        assert!(parse_policy("length").is_err());
+        assert!(parse_policy("unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_report() -> Result<()> {
+        let input =
+            Input::from("1-3 a: abcde\n1-3 b: cdefg\nnot a password\n2-9 c: ccccccccc".to_owned());
+        let policies = vec![parse_policy("frequency")?, parse_policy("positions")?];
+        let failures = report(&input, &policies);
+        assert_eq!(failures.len(), 4);
+        assert_eq!(
+            failures[..2],
+            [
+                "Line 2: 1-3 b: cdefg: 'b' appears 0 times, expected 1 to 3",
+                "Line 2: 1-3 b: cdefg: 'b' is at neither position 1 nor 3",
+            ],
+        );
+        assert!(failures[2].starts_with("Line 3: not a password: "));
+        assert_eq!(
+            failures[3],
+            "Line 4: 2-9 c: ccccccccc: 'c' is at both positions 2 and 9",
+        );
         Ok(())
     }
 }
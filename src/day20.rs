@@ -1,8 +1,10 @@
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet};
-use std::ops::Deref;
 use std::path::Path;
 
+use crate::coord::{Coord, Rect};
+use crate::reader::{read_records, Grid};
+
 #[derive(Debug, Clone)]
 struct Piece {
     top: Vec<bool>,
@@ -12,23 +14,12 @@ struct Piece {
 }
 
 impl Piece {
-    fn from_matrix<T: Deref<Target = [bool]>>(matrix: &[T]) -> Self {
-        let top = matrix[0].to_vec();
-        let bottom = matrix.last().unwrap().to_vec();
-
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-        for row in matrix {
-            left.push(row[0]);
-            right.push(*row.last().unwrap());
-        }
-
-        // NOTE: No sanity checks are done
+    fn from_grid(grid: &Grid<bool>) -> Self {
         Self {
-            top,
-            left,
-            right,
-            bottom,
+            top: grid.top_border(),
+            left: grid.left_border(),
+            right: grid.right_border(),
+            bottom: grid.bottom_border(),
         }
     }
 
@@ -42,96 +33,50 @@ impl Piece {
     }
 }
 
-fn all_matrix_transforms(matrix: &[Vec<bool>]) -> Vec<Vec<Vec<bool>>> {
-    let mut matrices = Vec::new();
-
-    for i in 0..8 {
-        let mut matrix = matrix.to_owned();
-
-        let transpose = (i >> 2) == 1;
-        let flip_x = (i >> 1) & 1 == 1;
-        let flip_y = i & 1 == 1;
-
-        if transpose {
-            let mut new_matrix = Vec::new();
-            for y in 0..matrix[0].len() {
-                let mut row = Vec::new();
-                #[allow(clippy::needless_range_loop)]
-                for x in 0..matrix.len() {
-                    row.push(matrix[x][y]);
-                }
-                new_matrix.push(row);
-            }
-            matrix = new_matrix;
-        }
-
-        if flip_x {
-            for row in matrix.iter_mut() {
-                row.reverse();
-            }
-        }
-
-        if flip_y {
-            matrix.reverse();
-        }
-
-        matrices.push(matrix);
-    }
-    matrices
-}
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
 
-fn num_non_sea_monster_pixels(image: &[bool], width: usize, height: usize) -> usize {
-    // The sea monster pattern looks like this. We translate it to the correct offsets
-    //                   #
-    // #    ##    ##    ###
-    //  #  #  #  #  #  #
-    let sea_monster_pattern = &[
-        18,
-        width,
-        width + 5,
-        width + 6,
-        width + 11,
-        width + 12,
-        width + 17,
-        width + 18,
-        width + 19,
-        2 * width + 1,
-        2 * width + 4,
-        2 * width + 7,
-        2 * width + 10,
-        2 * width + 13,
-        2 * width + 16,
-    ];
-    let mut sea_monster_offsets = HashSet::new();
-    for y in 0..height - 2 {
-        for x in 0..width - 19 {
-            let offset_base = y * width + x;
-            let aligned_offsets = sea_monster_pattern
-                .iter()
-                .map(|offset| offset_base + offset);
-            if aligned_offsets.clone().all(|offset| image[offset]) {
-                sea_monster_offsets.extend(aligned_offsets);
-            }
+fn num_non_sea_monster_pixels(image: &Grid<bool>) -> usize {
+    let pattern = SEA_MONSTER
+        .iter()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| Coord::new(x as isize, y as isize))
+        })
+        .collect::<Vec<_>>();
+    let monster = Rect::from_coords(pattern.iter().copied());
+
+    // Every top left corner where the whole sea monster fits inside the image
+    let bounds = image.bounds();
+    let corners = Rect::new(bounds.min, bounds.max - monster.max);
+    let mut sea_monster_pixels = HashSet::new();
+    for corner in corners.iter() {
+        let aligned = pattern.iter().map(|p| p + corner);
+        if aligned.clone().all(|c| image.get(&c) == Some(&true)) {
+            sea_monster_pixels.extend(aligned);
         }
     }
-    image.iter().filter(|&v| *v).count() - sea_monster_offsets.len()
+    image.tiles.iter().filter(|&v| *v).count() - sea_monster_pixels.len()
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let entries = std::fs::read_to_string(path)?;
-
-    let piece_matrices = entries
-        .split("\n\n")
-        .map(|piece| -> Result<_> {
-            let mut lines = piece.lines();
-            let id_str = lines.next().ok_or_else(|| anyhow!("No lines for piece"))?;
-            let id: usize = id_str[5..id_str.len() - 1].parse()?;
-            let matrix = lines
-                .map(|line| line.chars().map(|c| c == '#').collect::<Vec<_>>())
-                .collect::<Vec<_>>();
-            Ok((id, all_matrix_transforms(&matrix)))
-        })
-        .collect::<Result<HashMap<_, _>>>()?;
+    let piece_matrices = read_records(path, |piece| {
+        let id_str = piece.header();
+        let id: usize = id_str
+            .strip_prefix("Tile ")
+            .and_then(|s| s.strip_suffix(':'))
+            .ok_or_else(|| anyhow!("Invalid tile header {:?}", id_str))?
+            .parse()?;
+        let grid = Grid::<char>::from_lines(piece.body())?.map(|c| *c == '#');
+        Ok((id, grid.all_transforms()))
+    })?
+    .collect::<Result<HashMap<_, _>>>()?;
 
     // Generate a mapping from tile ID to a list of all variations of that piece. We only store the
     // edges and provide some convenience methods to check if two variations fit together.
@@ -140,10 +85,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
         .map(|(id, matrices)| {
             (
                 *id,
-                matrices
-                    .iter()
-                    .map(|matrix| Piece::from_matrix(matrix))
-                    .collect::<Vec<_>>(),
+                matrices.iter().map(Piece::from_grid).collect::<Vec<_>>(),
             )
         })
         .collect::<HashMap<_, _>>();
@@ -200,8 +142,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
         let mut used_pieces = HashMap::new();
         used_pieces.insert(*id, *variant);
 
-        let mut locations = Vec::new();
-        locations.push(*id);
+        let locations = vec![*id];
 
         queue.push((used_pieces, locations));
     }
@@ -209,9 +150,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
     // Search for possible tile configurations depth first by appending tiles to the right. If the
     // current tile it on the right hand side we check the tile below the start of the current row.
     let mut tile_configuration = None;
-    while !queue.is_empty() {
-        let (used_pieces, locations) = queue.pop().unwrap();
-
+    while let Some((used_pieces, locations)) = queue.pop() {
         // Check if we have managed to place all tiles
         if locations.len() == pieces.len() {
             let tiles = locations
@@ -228,7 +167,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
 
         // Get set of possible pieces that can go in the next location
         let alts = if x == side - 1 {
-            let above_id = locations[(y * side)];
+            let above_id = locations[y * side];
             &fits[&(above_id, used_pieces[&above_id])].3
         } else {
             let left_of_id = *locations.last().unwrap();
@@ -237,7 +176,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
 
         // Add new possible pieces to the queue stack
         for (other_id, other_variant_id) in alts {
-            if used_pieces.contains_key(&other_id) {
+            if used_pieces.contains_key(other_id) {
                 continue;
             }
 
@@ -259,39 +198,20 @@ pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
         * tile_configuration[tile_configuration.len() - side].0
         * tile_configuration[tile_configuration.len() - 1].0;
 
-    // Allocate all rows in the matrix that holds the final image with all borders between tiles
-    // removed
-    let tile_side = piece_matrices.values().next().unwrap()[0].len() - 2;
-    let mut full_image = Vec::new();
-    for _ in 0..side * tile_side {
-        full_image.push(Vec::with_capacity(side * tile_side));
-    }
-
-    // Fill final image with pixel values
-    for (i, (id, variant)) in tile_configuration.into_iter().enumerate() {
-        let tile_y = i / side;
-        for (j, row) in piece_matrices[&id][variant]
-            .iter()
-            .skip(1)
-            .take(tile_side)
-            .enumerate()
-        {
-            full_image[tile_y * tile_side + j].extend(row.iter().skip(1).take(tile_side).copied());
-        }
-    }
+    // Assemble the final image with all borders between tiles removed
+    let cropped_tiles = tile_configuration
+        .into_iter()
+        .map(|(id, variant)| piece_matrices[&id][variant].crop(1))
+        .collect::<Result<Vec<_>>>()?;
+    let full_image = Grid::stitch(&Grid::new(side, side, cropped_tiles)?)?;
 
     // Try all different transformations of final image, since it may be flipped incorrectly for
     // detecting sea monsters
-    let mut part_b = usize::MAX;
-    for image_variant in all_matrix_transforms(&full_image) {
-        let width = side * tile_side;
-        let pixels = image_variant
-            .into_iter()
-            .map(|line| line.into_iter())
-            .flatten()
-            .collect::<Vec<_>>();
-        part_b = part_b.min(num_non_sea_monster_pixels(&pixels, width, width));
-    }
+    let part_b = full_image
+        .all_views()
+        .map(|view| num_non_sea_monster_pixels(&view.to_grid()))
+        .min()
+        .unwrap();
 
     Ok((part_a, Some(part_b)))
 }
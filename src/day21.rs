@@ -2,24 +2,25 @@ use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use crate::reader::{read_lines, split_once};
+use crate::reader::{split_once, Input};
 
 pub fn main(path: &Path) -> Result<(usize, Option<String>)> {
-    let foods = read_lines(path)?
-        .map(|l| -> Result<(HashSet<String>, HashSet<String>)> {
-            let line = l?;
-            let (ingredients_str, allergenes_str) = split_once(&line, " (contains ");
+    let input = Input::from_path(path)?;
+    let foods = input
+        .lines()
+        .map(|line| -> (HashSet<&str>, HashSet<&str>) {
+            let (ingredients_str, allergenes_str) = split_once(line, " (contains ");
 
-            let ingredients = ingredients_str.split(' ').map(|i| i.to_owned()).collect();
+            let ingredients = ingredients_str.split(' ').collect();
             let allergenes = if let Some(s) = allergenes_str {
-                s[..s.len() - 1].split(", ").map(|a| a.to_owned()).collect()
+                s[..s.len() - 1].split(", ").collect()
             } else {
                 HashSet::new()
             };
 
-            Ok((ingredients, allergenes))
+            (ingredients, allergenes)
         })
-        .collect::<Result<Vec<_>>>()?;
+        .collect::<Vec<_>>();
 
     let mut ingredient_count = HashMap::new();
     let mut allergenes_to_ingredients = HashMap::new();
@@ -39,8 +40,7 @@ pub fn main(path: &Path) -> Result<(usize, Option<String>)> {
 
     let ingredients_with_allergenes = allergenes_to_ingredients
         .values()
-        .map(|i| i.iter())
-        .flatten()
+        .flat_map(|i| i.iter())
         .collect::<HashSet<_>>();
 
     let part_a = ingredient_count
@@ -1,8 +1,8 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use std::collections::{HashSet, VecDeque};
 use std::path::Path;
 
-use crate::reader::split_once;
+use crate::reader::{read_typed_sections, Section};
 
 #[derive(Debug)]
 enum Winner {
@@ -25,6 +25,10 @@ impl Winner {
     }
 }
 
+fn parse_deck(section: &Section) -> Result<VecDeque<usize>> {
+    section.body().iter().map(|l| Ok(l.parse()?)).collect()
+}
+
 fn combat(mut player_1: VecDeque<usize>, mut player_2: VecDeque<usize>) -> Winner {
     while !player_1.is_empty() && !player_2.is_empty() {
         let a = player_1.pop_front().unwrap();
@@ -94,20 +98,10 @@ fn recursive_combat(mut player_1: VecDeque<usize>, mut player_2: VecDeque<usize>
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let input = std::fs::read_to_string(path)?;
-    let (player_1_str, player_2_str) = split_once(&input, "\n\n");
+    let (player_1_section, player_2_section): (Section, Section) = read_typed_sections(path)?;
 
-    let player_1 = player_1_str
-        .lines()
-        .skip(1)
-        .map(|l| Ok(l.parse()?))
-        .collect::<Result<VecDeque<usize>>>()?;
-    let player_2 = player_2_str
-        .ok_or_else(|| anyhow!("No input for player 2"))?
-        .lines()
-        .skip(1)
-        .map(|l| Ok(l.parse()?))
-        .collect::<Result<VecDeque<usize>>>()?;
+    let player_1 = player_1_section.parse_with(parse_deck)?;
+    let player_2 = player_2_section.parse_with(parse_deck)?;
 
     let part_a = combat(player_1.clone(), player_2.clone());
     let part_b = recursive_combat(player_1, player_2);
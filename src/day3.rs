@@ -1,35 +1,196 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::RangeInclusive;
 use std::path::Path;
 
 use crate::coord::Coord;
-use crate::reader::Grid;
+use crate::reader::{Edge, EdgeView, Grid};
 
-fn is_tree(map: &Grid, coord: &Coord) -> bool {
-    let x = coord.x % map.width as isize;
-    map.tiles.get(&Coord::new(x, coord.y)) == Some(&'#')
+/// Steps taken right and down on every move. Negative right steps go left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Slope {
+    pub right: isize,
+    pub down: isize,
 }
 
-fn is_in_bounds(map: &Grid, coord: &Coord) -> bool {
-    coord.y >= 0 && coord.y < map.height as isize
+impl Slope {
+    pub fn new(right: isize, down: isize) -> Self {
+        Self { right, down }
+    }
 }
 
-fn num_trees_encountered(map: &Grid, x_step: isize, y_step: isize) -> usize {
-    (1..)
-        .into_iter()
-        .map(|i| Coord::new(i * x_step, i * y_step))
-        .take_while(|c| is_in_bounds(map, c))
-        .filter(|c| is_tree(map, c))
-        .count()
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "right {}, down {}", self.right, self.down)
+    }
+}
+
+fn is_tree(map: &EdgeView<char>, coord: &Coord) -> bool {
+    map.get(coord) == Some(&'#')
+}
+
+fn is_in_bounds(map: &EdgeView<char>, coord: &Coord) -> bool {
+    map.get(coord).is_some()
+}
+
+/// Coordinates visited after the start until the bottom of the map. They aren't wrapped, so x
+/// may be outside of the map.
+fn path<'a>(map: &'a EdgeView<char>, slope: Slope) -> Result<impl Iterator<Item = Coord> + 'a> {
+    if slope.down <= 0 {
+        return Err(anyhow!("Slope {} never reaches the bottom", slope));
+    }
+    Ok((1..)
+        .map(move |i| Coord::new(i * slope.right, i * slope.down))
+        .take_while(move |c| is_in_bounds(map, c)))
+}
+
+pub fn num_trees_encountered(map: &Grid, slope: Slope) -> Result<usize> {
+    // The map repeats to the right
+    let map = map.with_edges(Edge::Wrap, Edge::Bounded);
+    let num_trees = path(&map, slope)?.filter(|c| is_tree(&map, c)).count();
+    Ok(num_trees)
+}
+
+/// Number of trees for every combination of steps, from fewest to most. Slopes that don't go
+/// down never reach the bottom, so downs of zero or less are skipped.
+pub fn rank_slopes(
+    map: &Grid,
+    rights: RangeInclusive<isize>,
+    downs: RangeInclusive<isize>,
+) -> Result<Vec<(Slope, usize)>> {
+    let mut ranking = Vec::new();
+    for down in downs.filter(|d| *d > 0) {
+        for right in rights.clone() {
+            let slope = Slope::new(right, down);
+            ranking.push((slope, num_trees_encountered(map, slope)?));
+        }
+    }
+    ranking.sort_by_key(|(slope, trees)| (*trees, *slope));
+    Ok(ranking)
+}
+
+/// Slope in the ranges that hits the fewest trees
+pub fn fewest_trees(
+    map: &Grid,
+    rights: RangeInclusive<isize>,
+    downs: RangeInclusive<isize>,
+) -> Result<Option<(Slope, usize)>> {
+    Ok(rank_slopes(map, rights, downs)?.into_iter().next())
+}
+
+/// Draw the map with every visited open square as `O` and every tree hit as `X`
+pub fn render_path(map: &Grid, slope: Slope) -> Result<String> {
+    let view = map.with_edges(Edge::Wrap, Edge::Bounded);
+    let width = map.width as isize;
+    let visited = path(&view, slope)?
+        .map(|c| Coord::new(c.x.rem_euclid(width), c.y))
+        .collect::<HashSet<_>>();
+    let overlay = Grid::from_fn(map.width, map.height, |c| {
+        match (visited.contains(&c), map.get(&c)) {
+            (true, Some('#')) => 'X',
+            (true, _) => 'O',
+            (false, tile) => *tile.unwrap(),
+        }
+    });
+    Ok(overlay.to_string())
+}
+
+/// Parse inclusive ranges like `-3..=3`, or a single number
+fn parse_range(s: &str) -> Result<RangeInclusive<isize>> {
+    let parse = |n: &str| n.parse().with_context(|| format!("Invalid range {:?}", s));
+    match s.split_once("..=") {
+        Some((start, end)) => Ok(parse(start)?..=parse(end)?),
+        None => {
+            let n = parse(s)?;
+            Ok(n..=n)
+        }
+    }
+}
+
+/// Rank the slopes in a range with `rank <rights> <downs>`, or draw one with `show <right> <down>`.
+/// Ranges are inclusive like `-3..=3`.
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let map: Grid = Grid::from_path(path)?;
+    match options {
+        [mode, rights, downs] if mode == "rank" => {
+            let ranking = rank_slopes(&map, parse_range(rights)?, parse_range(downs)?)?;
+            Ok(ranking
+                .iter()
+                .map(|(slope, trees)| format!("{}: {} trees", slope, trees))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+        [mode, right, down] if mode == "show" => {
+            let slope = Slope::new(right.parse()?, down.parse()?);
+            Ok(format!(
+                "{} trees\n{}",
+                num_trees_encountered(&map, slope)?,
+                render_path(&map, slope)?,
+            ))
+        }
+        _ => Err(anyhow!(
+            "Expected options: rank <rights> <downs> or show <right> <down>"
+        )),
+    }
 }
 
 pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map = Grid::from_path(path)?;
-    let part_a = num_trees_encountered(&map, 3, 1);
+    let map: Grid = Grid::from_path(path)?;
+    let part_a = num_trees_encountered(&map, Slope::new(3, 1))?;
 
     let mut part_b = part_a;
-    for (x_step, y_step) in &[(1, 1), (5, 1), (7, 1), (1, 2)] {
-        part_b *= num_trees_encountered(&map, *x_step, *y_step);
+    for (right, down) in &[(1, 1), (5, 1), (7, 1), (1, 2)] {
+        part_b *= num_trees_encountered(&map, Slope::new(*right, *down))?;
     }
 
     Ok((part_a, Some(part_b)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Result<Grid> {
+        Grid::from_lines([
+            "..##.......",
+            "#...#...#..",
+            ".#....#..#.",
+            "..#.#...#.#",
+            ".#...##..#.",
+            "..#.##.....",
+            ".#.#.#....#",
+            ".#........#",
+            "#.##...#...",
+            "#...##....#",
+            ".#..#...#.#",
+        ])
+    }
+
+    #[test]
+    fn test_slopes() -> Result<()> {
+        let map = example()?;
+        assert_eq!(num_trees_encountered(&map, Slope::new(3, 1))?, 7);
+        assert_eq!(num_trees_encountered(&map, Slope::new(1, 2))?, 2);
+        // Going left wraps around to the right side of the map
+        assert_eq!(num_trees_encountered(&map, Slope::new(-1, 1))?, 5);
+        assert!(num_trees_encountered(&map, Slope::new(1, 0)).is_err());
+
+        let ranking = rank_slopes(&map, 1..=7, 1..=2)?;
+        assert_eq!(ranking.len(), 14);
+        assert!(ranking.contains(&(Slope::new(5, 1), 3)));
+        assert_eq!(ranking.last(), Some(&(Slope::new(3, 1), 7)));
+        assert_eq!(fewest_trees(&map, 1..=7, 1..=2)?, Some(ranking[0]));
+        assert_eq!(rank_slopes(&map, 1..=7, -1..=2)?, ranking);
+        assert_eq!(parse_range("-3..=3")?, -3..=3);
+        assert!(parse_range("-3..3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_path() -> Result<()> {
+        let map = Grid::from_lines(["..#", "#.#", "#.."])?;
+        assert_eq!(render_path(&map, Slope::new(-1, 1))?, "..#\n#.X\n#O.\n");
+        Ok(())
+    }
+}
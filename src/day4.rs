@@ -1,114 +1,285 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
-use thiserror::Error;
 
-use crate::reader::read_lines;
+use crate::reader::{Input, Section};
+use crate::schema::{Schema, Violation};
 
-#[derive(Error, Debug)]
-enum PassportError {
-    #[error("missing field {0:?}")]
-    Missing(String),
+/// Passport rules from the puzzle, used unless another schema is given
+pub const DEFAULT_SCHEMA: &str = r"
+byr required int 1920-2002
+iyr required int 2010-2020
+eyr required int 2020-2030
+hgt required unit 150-193cm 59-76in
+hcl required regex #[a-f0-9]{6}
+ecl required enum amb blu brn gry grn hzl oth
+pid required regex \d{9}
+cid optional
+";
 
-    #[error("invalid value {1:?} for field {0:?}")]
-    Invalid(String, String),
-}
-
-static HEIGHT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+)(cm|in)$").unwrap());
-static COLOR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#[a-f0-9]{6}$").unwrap());
-static EYE_COLOR_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(amb|blu|brn|gry|grn|hzl|oth)$").unwrap());
-static PID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{9}$").unwrap());
+static FIELD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"([^: ]+):(\S+)").unwrap());
 
-fn get_field<'a>(map: &'a HashMap<String, String>, key: &str) -> Result<&'a str, PassportError> {
-    map.get(key)
-        .ok_or_else(|| PassportError::Missing(key.to_owned()))
-        .map(String::as_str)
+/// Map from field to value of a passport spread over the lines of a record
+fn parse_passport<'a>(record: &Section<&'a str>) -> HashMap<&'a str, &'a str> {
+    let mut passport = HashMap::new();
+    for line in &record.lines {
+        for c in FIELD_RE.captures_iter(line) {
+            let (key, value) = (c.get(1).unwrap(), c.get(2).unwrap());
+            passport.insert(key.as_str(), value.as_str());
+        }
+    }
+    passport
 }
 
-fn validate_passport(passport: &HashMap<String, String>) -> Result<(), PassportError> {
-    // We need to extract the all fields before value validation since part A
-    // only requires the field to be present
-    let byr = get_field(passport, "byr")?;
-    let iyr = get_field(passport, "iyr")?;
-    let eyr = get_field(passport, "eyr")?;
-    let hgt = get_field(passport, "hgt")?;
-    let hcl = get_field(passport, "hcl")?;
-    let ecl = get_field(passport, "ecl")?;
-    let pid = get_field(passport, "pid")?;
-
-    if !(1920..=2002).contains(&byr.parse::<usize>().unwrap_or(0)) {
-        return Err(PassportError::Invalid("byr".to_owned(), byr.into()));
+/// Number of passports with all required fields, and the number of those that are also valid
+fn count_valid(input: &Input, schema: &Schema) -> (usize, usize) {
+    let mut num_complete = 0;
+    let mut num_valid = 0;
+    for passport in input.records().map(|r| parse_passport(&r)) {
+        if schema.is_complete(&passport) {
+            num_complete += 1;
+            if schema.is_valid(&passport) {
+                num_valid += 1;
+            }
+        }
     }
+    (num_complete, num_valid)
+}
+
+/// Everything wrong with a single passport
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PassportReport {
+    /// Line number (starting from 1) where the passport starts
+    line_no: usize,
+    /// Every key and value of the passport, sorted by key
+    fields: Vec<(String, String)>,
+    violations: Vec<Violation>,
+}
 
-    if !(2010..=2020).contains(&iyr.parse::<usize>().unwrap_or(0)) {
-        return Err(PassportError::Invalid("iyr".to_owned(), iyr.into()));
+impl PassportReport {
+    fn is_valid(&self) -> bool {
+        self.violations.iter().all(|v| !v.is_error())
     }
 
-    if !(2020..=2030).contains(&eyr.parse::<usize>().unwrap_or(0)) {
-        return Err(PassportError::Invalid("eyr".to_owned(), eyr.into()));
+    fn reasons(&self) -> Vec<String> {
+        self.violations.iter().map(|v| v.to_string()).collect()
     }
+}
 
-    HEIGHT_RE
-        .captures(hgt)
-        .map(|c| match &c[2] {
-            "cm" if (150..=193).contains(&c[1].parse::<usize>().unwrap_or(0)) => Some(()),
-            "in" if (59..=76).contains(&c[1].parse::<usize>().unwrap_or(0)) => Some(()),
-            _ => None,
+fn report(input: &Input, schema: &Schema) -> Vec<PassportReport> {
+    input
+        .records()
+        .map(|r| {
+            let passport = parse_passport(&r);
+            let mut fields = passport
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>();
+            fields.sort_unstable();
+            PassportReport {
+                line_no: r.line_no,
+                fields,
+                violations: schema.violations(&passport),
+            }
         })
-        .flatten()
-        .ok_or_else(|| PassportError::Invalid("hgt".to_owned(), hgt.into()))?;
+        .collect()
+}
 
-    if !COLOR_RE.is_match(hcl) {
-        return Err(PassportError::Invalid("hcl".to_owned(), hcl.into()));
-    }
+fn quote_csv(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
 
-    if !EYE_COLOR_RE.is_match(ecl) {
-        return Err(PassportError::Invalid("ecl".to_owned(), ecl.into()));
+fn quote_json(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
     }
+    out.push('"');
+    out
+}
 
-    if !PID_RE.is_match(pid) {
-        return Err(PassportError::Invalid("pid".to_owned(), pid.into()));
+/// One row per passport, with the fields written as `key:value` pairs like in the input
+fn to_csv(reports: &[PassportReport]) -> String {
+    let mut out = String::from("line,valid,fields,reasons\n");
+    for r in reports {
+        let fields = r
+            .fields
+            .iter()
+            .map(|(k, v)| format!("{}:{}", k, v))
+            .collect::<Vec<_>>();
+        out += &format!(
+            "{},{},{},{}\n",
+            r.line_no,
+            r.is_valid(),
+            quote_csv(&fields.join(" ")),
+            quote_csv(&r.reasons().join("; ")),
+        );
     }
+    out
+}
 
-    Ok(())
+fn to_json(reports: &[PassportReport]) -> String {
+    let entries = reports
+        .iter()
+        .map(|r| {
+            let fields = r
+                .fields
+                .iter()
+                .map(|(k, v)| format!("{}: {}", quote_json(k), quote_json(v)))
+                .collect::<Vec<_>>();
+            let reasons = r
+                .reasons()
+                .iter()
+                .map(|s| quote_json(s))
+                .collect::<Vec<_>>();
+            format!(
+                "  {{\"line\": {}, \"valid\": {}, \"fields\": {{{}}}, \"reasons\": [{}]}}",
+                r.line_no,
+                r.is_valid(),
+                fields.join(", "),
+                reasons.join(", "),
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[\n{}\n]\n", entries.join(",\n"))
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    // Read all passports in a vector of maps from field to value
-    let re = Regex::new(r"([^: ]+):(\S+)")?;
-    let mut passports = Vec::new();
-    passports.push(HashMap::new());
-
-    for line in read_lines(path)? {
-        let line = line?;
-        if line == "" {
-            passports.push(HashMap::new());
-        }
-        for c in re.captures_iter(&line) {
-            passports
-                .last_mut()
-                .unwrap()
-                .insert(c[1].to_owned(), c[2].to_owned());
+/// Count passports with `count`, list every passport with violations with `report`, or export
+/// all of them with `export csv` or `export json`. Any of these can be followed by
+/// `schema <path>` to use another schema.
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let (options, schema) = match options {
+        [rest @ .., flag, schema] if flag == "schema" => (rest, Schema::from_path(schema)?),
+        _ => (options, DEFAULT_SCHEMA.parse()?),
+    };
+    let input = Input::from_path(path)?;
+    match options {
+        [] => {
+            let (num_complete, num_valid) = count_valid(&input, &schema);
+            Ok(format!("{} complete, {} valid", num_complete, num_valid))
         }
+        [mode] if mode == "report" => Ok(report(&input, &schema)
+            .iter()
+            .filter(|r| !r.violations.is_empty())
+            .map(|r| format!("Line {}: {}", r.line_no, r.reasons().join(", ")))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        [mode, format] if mode == "export" => match format.as_str() {
+            "csv" => Ok(to_csv(&report(&input, &schema))),
+            "json" => Ok(to_json(&report(&input, &schema))),
+            _ => Err(anyhow!("Unknown export format {:?}", format)),
+        },
+        _ => Err(anyhow!(
+            "Expected options: [report | export <csv|json>] [schema <path>]"
+        )),
     }
+}
 
-    let mut num_valid_a = 0;
-    let mut num_valid_b = 0;
-    for p in passports {
-        match validate_passport(&p) {
-            Ok(_) => {
-                num_valid_a += 1;
-                num_valid_b += 1;
-            }
-            Err(PassportError::Missing(_)) => {
-                num_valid_a += 1;
-            }
-            Err(_) => {}
-        }
+pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
+    let schema = DEFAULT_SCHEMA.parse()?;
+    let (num_complete, num_valid) = count_valid(&Input::from_path(path)?, &schema);
+    Ok((num_complete, Some(num_valid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default schema without cid, so that it is reported as unknown
+    const STRICT_SCHEMA: &str = r"
+byr required int 1920-2002
+iyr required int 2010-2020
+eyr required int 2020-2030
+hgt required unit 150-193cm 59-76in
+hcl required regex #[a-f0-9]{6}
+ecl required enum amb blu brn gry grn hzl oth
+pid required regex \d{9}
+";
+
+    #[test]
+    fn test_example() -> Result<()> {
+        let input = Input::from(
+            "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+"
+            .to_string(),
+        );
+        assert_eq!(count_valid(&input, &DEFAULT_SCHEMA.parse()?), (2, 1));
+
+        let reports = report(&input, &DEFAULT_SCHEMA.parse()?);
+        assert_eq!(
+            reports.iter().map(|r| r.line_no).collect::<Vec<_>>(),
+            vec![1, 4, 7],
+        );
+        assert_eq!(
+            reports[0].reasons(),
+            vec![
+                "invalid value \"1972\" for field \"eyr\"".to_string(),
+                "invalid value \"170\" for field \"hgt\"".to_string(),
+                "invalid value \"186cm\" for field \"pid\"".to_string(),
+            ],
+        );
+        assert_eq!(
+            reports[0].fields[0],
+            ("byr".to_string(), "1926".to_string())
+        );
+        assert!(reports[1].violations.is_empty());
+        assert_eq!(
+            reports[2].violations,
+            vec![Violation::Missing("byr".to_string())]
+        );
+
+        let reports = report(&input, &STRICT_SCHEMA.parse()?);
+        assert_eq!(
+            reports[0].violations.last(),
+            Some(&Violation::Unknown("cid".to_string())),
+        );
+        Ok(())
     }
 
-    Ok((num_valid_a, Some(num_valid_b)))
+    #[test]
+    fn test_export() {
+        let reports = vec![
+            PassportReport {
+                line_no: 1,
+                fields: vec![("iyr".to_string(), "2015".to_string())],
+                violations: vec![Violation::Missing("byr".to_string())],
+            },
+            PassportReport {
+                line_no: 3,
+                fields: vec![
+                    ("byr".to_string(), "1980".to_string()),
+                    ("cid".to_string(), "7".to_string()),
+                ],
+                violations: vec![Violation::Unknown("cid".to_string())],
+            },
+        ];
+        assert_eq!(
+            to_csv(&reports),
+            "line,valid,fields,reasons\n\
+             1,false,\"iyr:2015\",\"missing field \"\"byr\"\"\"\n\
+             3,true,\"byr:1980 cid:7\",\"unknown field \"\"cid\"\"\"\n",
+        );
+        assert_eq!(
+            to_json(&reports),
+            "[\n  {\"line\": 1, \"valid\": false, \"fields\": {\"iyr\": \"2015\"}, \
+             \"reasons\": [\"missing field \\\"byr\\\"\"]},\n  \
+             {\"line\": 3, \"valid\": true, \"fields\": {\"byr\": \"1980\", \"cid\": \"7\"}, \
+             \"reasons\": [\"unknown field \\\"cid\\\"\"]}\n]\n",
+        );
+    }
 }
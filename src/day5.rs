@@ -1,40 +1,219 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
 use std::path::Path;
 
-use crate::reader::read_mapped_lines;
+use crate::reader::{read_mapped_lines, Grid, ToChar};
 
-fn seat_spec_to_id(seat: &str) -> Result<usize> {
-    // NOTE: We don't validate the order and length of the seat number
-    let bin_repr = seat
-        .chars()
-        .map(|c| match c {
-            'F' | 'L' => Ok('0'),
-            'B' | 'R' => Ok('1'),
-            e => Err(anyhow!("Invalid character {:?} in seat number", e)),
+/// Encodes seat IDs as boarding pass specs like "FBFBBFFRLR". The row is given by F (front) and
+/// B (back) and the column by L (left) and R (right), both as binary numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeatCodec {
+    row_bits: usize,
+    column_bits: usize,
+}
+
+/// Most bits a codec may use in total, which keeps the seat map to about a million seats
+pub const MAX_BITS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Seat {
+    Occupied,
+    Empty,
+    /// Empty seat with occupied seats somewhere in front and behind it
+    Gap,
+}
+
+impl Default for SeatCodec {
+    /// The plane from the puzzle with 128 rows of 8 seats
+    fn default() -> Self {
+        Self {
+            row_bits: 7,
+            column_bits: 3,
+        }
+    }
+}
+
+impl SeatCodec {
+    /// Fails if the codec would use more than `MAX_BITS` bits
+    pub fn new(row_bits: usize, column_bits: usize) -> Result<Self> {
+        if row_bits.saturating_add(column_bits) > MAX_BITS {
+            return Err(anyhow!(
+                "Seat specs can have at most {} bits, got {} row and {} column bits",
+                MAX_BITS,
+                row_bits,
+                column_bits,
+            ));
+        }
+        Ok(Self {
+            row_bits,
+            column_bits,
         })
-        .collect::<Result<String>>()?;
-    Ok(usize::from_str_radix(&bin_repr, 2)?)
+    }
+
+    pub fn row_bits(&self) -> usize {
+        self.row_bits
+    }
+
+    pub fn column_bits(&self) -> usize {
+        self.column_bits
+    }
+
+    pub fn num_rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    pub fn num_columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    pub fn num_seats(&self) -> usize {
+        self.num_rows() * self.num_columns()
+    }
+
+    /// Decode a spec, which must have exactly the right number of F/B followed by L/R
+    pub fn decode(&self, spec: &str) -> Result<usize> {
+        let len = spec.chars().count();
+        if len != self.row_bits + self.column_bits {
+            return Err(anyhow!(
+                "Expected {} characters in seat spec {:?}, found {}",
+                self.row_bits + self.column_bits,
+                spec,
+                len,
+            ));
+        }
+        spec.chars().enumerate().try_fold(0, |id, (i, c)| {
+            let bit = match (i < self.row_bits, c) {
+                (true, 'F') | (false, 'L') => 0,
+                (true, 'B') | (false, 'R') => 1,
+                (true, c) => {
+                    return Err(anyhow!(
+                        "Invalid character {:?} at position {}, expected F or B",
+                        c,
+                        i + 1,
+                    ))
+                }
+                (false, c) => {
+                    return Err(anyhow!(
+                        "Invalid character {:?} at position {}, expected L or R",
+                        c,
+                        i + 1,
+                    ))
+                }
+            };
+            Ok(id << 1 | bit)
+        })
+    }
+
+    pub fn encode(&self, id: usize) -> Result<String> {
+        if id >= self.num_seats() {
+            return Err(anyhow!(
+                "Seat ID {} is outside of a plane with {} seats",
+                id,
+                self.num_seats(),
+            ));
+        }
+        let total_bits = self.row_bits + self.column_bits;
+        Ok((0..total_bits)
+            .map(|i| {
+                let is_set = id >> (total_bits - i - 1) & 1 == 1;
+                match (i < self.row_bits, is_set) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                }
+            })
+            .collect())
+    }
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let mut seat_ids = read_mapped_lines(path, seat_spec_to_id)?;
-    let mut min = seat_ids
-        .next()
-        .transpose()?
-        .ok_or_else(|| anyhow!("No seat IDs"))?;
-    let mut max = min;
-    let mut sum = min;
+impl ToChar for Seat {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Occupied => '#',
+            Self::Empty => '.',
+            Self::Gap => 'O',
+        }
+    }
+}
+
+/// IDs that appear more than once, in order of their second appearance
+pub fn duplicates(ids: &[usize]) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    ids.iter()
+        .filter(|id| !seen.insert(**id))
+        .copied()
+        .collect()
+}
+
+/// Missing IDs between the lowest and highest occupied seat
+pub fn gaps(ids: &[usize]) -> Vec<usize> {
+    let occupied = ids.iter().copied().collect::<HashSet<_>>();
+    match (ids.iter().min(), ids.iter().max()) {
+        (Some(min), Some(max)) => (*min..*max).filter(|id| !occupied.contains(id)).collect(),
+        _ => Vec::new(),
+    }
+}
 
-    for seat_id in seat_ids {
-        let seat_id = seat_id?;
-        if seat_id < min {
-            min = seat_id;
-        } else if seat_id > max {
-            max = seat_id;
+/// Draw every seat with one row per line, marking occupied seats with `#` and gaps with `O`
+fn seat_map(codec: &SeatCodec, ids: &[usize]) -> String {
+    let occupied = ids.iter().copied().collect::<HashSet<_>>();
+    let gaps = gaps(ids).into_iter().collect::<HashSet<_>>();
+    Grid::from_fn(codec.num_columns(), codec.num_rows(), |c| {
+        let id = c.y as usize * codec.num_columns() + c.x as usize;
+        if occupied.contains(&id) {
+            Seat::Occupied
+        } else if gaps.contains(&id) {
+            Seat::Gap
+        } else {
+            Seat::Empty
         }
-        sum += seat_id;
+    })
+    .to_string()
+}
+
+fn read_ids(path: &Path, codec: SeatCodec) -> Result<Vec<usize>> {
+    read_mapped_lines(path, move |l| codec.decode(l))?.collect()
+}
+
+/// Draw the seat map with `map [row_bits column_bits]` or encode a seat ID with `encode <id>`
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    match options {
+        [mode, rest @ ..] if mode == "map" => {
+            let codec = match rest {
+                [] => SeatCodec::default(),
+                [row_bits, column_bits] => SeatCodec::new(
+                    row_bits.parse().context("Invalid row bits")?,
+                    column_bits.parse().context("Invalid column bits")?,
+                )?,
+                _ => return Err(anyhow!("Expected options: map [row_bits column_bits]")),
+            };
+            let ids = read_ids(path, codec)?;
+            Ok(format!(
+                "{}Duplicates: {:?}\nGaps: {:?}",
+                seat_map(&codec, &ids),
+                duplicates(&ids),
+                gaps(&ids),
+            ))
+        }
+        [mode, id] if mode == "encode" => SeatCodec::default().encode(id.parse()?),
+        _ => Err(anyhow!(
+            "Expected options: map [row_bits column_bits] or encode <id>"
+        )),
+    }
+}
+
+pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
+    let ids = read_ids(path, SeatCodec::default())?;
+    if let Some(id) = duplicates(&ids).first() {
+        return Err(anyhow!("Seat {} is on more than one boarding pass", id));
     }
-    Ok((max, Some((min..=max).into_iter().sum::<usize>() - sum)))
+    let max = ids.iter().max().ok_or_else(|| anyhow!("No seat IDs"))?;
+    let seat = match gaps(&ids).as_slice() {
+        [seat] => *seat,
+        gaps => return Err(anyhow!("Expected a single free seat, found {:?}", gaps)),
+    };
+    Ok((*max, Some(seat)))
 }
 
 #[cfg(test)]
@@ -43,9 +222,42 @@ mod tests {
 
     #[test]
     fn test_seat_spec_to_id() -> Result<()> {
-        assert_eq!(seat_spec_to_id("BFFFBBFRRR")?, 567);
-        assert_eq!(seat_spec_to_id("FFFBBBFRRR")?, 119);
-        assert_eq!(seat_spec_to_id("BBFFBBFRLL")?, 820);
+        let codec = SeatCodec::default();
+        assert_eq!(codec.decode("BFFFBBFRRR")?, 567);
+        assert_eq!(codec.decode("FFFBBBFRRR")?, 119);
+        assert_eq!(codec.decode("BBFFBBFRLL")?, 820);
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec() -> Result<()> {
+        let codec = SeatCodec::default();
+        assert_eq!(codec.encode(567)?, "BFFFBBFRRR");
+        for id in 0..codec.num_seats() {
+            assert_eq!(codec.decode(&codec.encode(id)?)?, id);
+        }
+        assert!(codec.encode(1024).is_err());
+
+        assert!(codec.decode("BFFFBBFRR").is_err());
+        assert!(codec.decode("BFFFBBRFRR").is_err());
+        assert!(codec.decode("BFFFBBFRRB").is_err());
+
+        let small = SeatCodec::new(2, 1)?;
+        assert_eq!(small.encode(5)?, "BFR");
+        assert_eq!(small.decode("BBL")?, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seat_map() -> Result<()> {
+        let codec = SeatCodec::new(2, 2)?;
+        let ids = [1, 2, 4, 7, 8, 2];
+        assert_eq!(duplicates(&ids), vec![2]);
+        assert_eq!(gaps(&ids), vec![3, 5, 6]);
+        assert_eq!(seat_map(&codec, &ids), ".##O\n#OO#\n#...\n....\n");
+        assert!(SeatCodec::new(10, 10).is_ok());
+        assert!(SeatCodec::new(60, 10).is_err());
+        assert!(SeatCodec::new(usize::MAX, 1).is_err());
         Ok(())
     }
 }
@@ -1,8 +1,9 @@
-use anyhow::Result;
-use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
+use std::str::FromStr;
 
-use crate::reader::read_lines;
+use crate::reader::read_records;
 
 #[derive(Debug, Default, PartialEq, Eq)]
 struct Group {
@@ -10,6 +11,40 @@ struct Group {
     answers: HashMap<char, usize>,
 }
 
+/// How many people in a group must have answered yes to a question
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quorum {
+    AtLeast(usize),
+    /// At least this percentage of the group, rounding up
+    Percent(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+/// Query over all groups. The syntax is one of:
+///
+/// - `count <quorum>`: number of questions meeting the quorum, summed over all groups
+/// - `histogram`: number of people and groups that answered yes to each question
+/// - `groups <n> [<union|intersect|minus> <n>]... [where <quorum>]`: set algebra between the
+///   questions of groups meeting the quorum, with groups numbered from 1
+///
+/// A quorum is `any`, `all`, a number of people like `3` or a percentage like `50%`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Query {
+    Count(Quorum),
+    Histogram,
+    Groups {
+        first: usize,
+        ops: Vec<(SetOp, usize)>,
+        quorum: Quorum,
+    },
+}
+
 impl Group {
     fn new() -> Self {
         Default::default()
@@ -22,29 +57,183 @@ impl Group {
         }
     }
 
+    /// Questions answered with yes by enough people
+    fn questions(&self, quorum: Quorum) -> BTreeSet<char> {
+        self.answers
+            .iter()
+            .filter(|(_, n)| quorum.is_met(**n, self.num_people))
+            .map(|(c, _)| *c)
+            .collect()
+    }
+
+    fn num_yes(&self, quorum: Quorum) -> usize {
+        self.questions(quorum).len()
+    }
+
     fn num_unique_yes(&self) -> usize {
-        self.answers.len()
+        self.num_yes(Quorum::AtLeast(1))
     }
 
     fn num_unanimous_yes(&self) -> usize {
-        self.answers
-            .values()
-            .filter(|n| **n == self.num_people)
-            .count()
+        self.num_yes(Quorum::Percent(100))
     }
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let mut groups = Vec::new();
-    groups.push(Group::new());
-    for line in read_lines(path)? {
-        let line = line?;
-        if line == "" {
-            groups.push(Group::new());
-            continue;
+impl Quorum {
+    fn is_met(&self, num_yes: usize, num_people: usize) -> bool {
+        match self {
+            Self::AtLeast(k) => num_yes >= *k,
+            Self::Percent(p) => num_yes * 100 >= p * num_people,
         }
-        groups.last_mut().unwrap().add_person(line.chars());
     }
+}
+
+impl FromStr for Quorum {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "any" => Self::AtLeast(1),
+            "all" => Self::Percent(100),
+            _ => match s.strip_suffix('%') {
+                Some(p) => {
+                    let p = p
+                        .parse()
+                        .with_context(|| format!("Invalid quorum {:?}", s))?;
+                    if p > 100 {
+                        return Err(anyhow!("Quorum {:?} is more than 100%", s));
+                    }
+                    Self::Percent(p)
+                }
+                None => Self::AtLeast(
+                    s.parse()
+                        .with_context(|| format!("Invalid quorum {:?}", s))?,
+                ),
+            },
+        })
+    }
+}
+
+impl FromStr for SetOp {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "union" => Self::Union,
+            "intersect" => Self::Intersection,
+            "minus" => Self::Difference,
+            _ => return Err(anyhow!("Unknown set operation {:?}", s)),
+        })
+    }
+}
+
+fn parse_group_number(s: &str) -> Result<usize> {
+    match s.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(anyhow!("Invalid group number {:?}", s)),
+    }
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        Ok(match words.as_slice() {
+            ["count", quorum] => Self::Count(quorum.parse()?),
+            ["histogram"] => Self::Histogram,
+            ["groups", first, rest @ ..] => {
+                let (rest, quorum) = match rest {
+                    [rest @ .., "where", quorum] => (rest, quorum.parse()?),
+                    _ => (rest, Quorum::AtLeast(1)),
+                };
+                if rest.len() % 2 != 0 {
+                    return Err(anyhow!("Expected a group after {:?}", rest.last().unwrap()));
+                }
+                let ops = rest
+                    .chunks(2)
+                    .map(|pair| Ok((pair[0].parse()?, parse_group_number(pair[1])?)))
+                    .collect::<Result<_>>()?;
+                Self::Groups {
+                    first: parse_group_number(first)?,
+                    ops,
+                    quorum,
+                }
+            }
+            _ => return Err(anyhow!("Invalid query {:?}", s)),
+        })
+    }
+}
+
+impl Query {
+    fn run(&self, groups: &[Group]) -> Result<String> {
+        match self {
+            Self::Count(quorum) => Ok(groups
+                .iter()
+                .map(|g| g.num_yes(*quorum))
+                .sum::<usize>()
+                .to_string()),
+            Self::Histogram => Ok(histogram(groups)
+                .iter()
+                .map(|(c, (people, groups))| {
+                    format!("{}: {} people in {} groups", c, people, groups)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+            Self::Groups { first, ops, quorum } => {
+                let questions = |n: usize| {
+                    groups
+                        .get(n - 1)
+                        .map(|g| g.questions(*quorum))
+                        .ok_or_else(|| anyhow!("There is no group {}", n))
+                };
+                let mut result = questions(*first)?;
+                for (op, n) in ops {
+                    let other = questions(*n)?;
+                    result = match op {
+                        SetOp::Union => &result | &other,
+                        SetOp::Intersection => &result & &other,
+                        SetOp::Difference => &result - &other,
+                    };
+                }
+                Ok(result.into_iter().collect())
+            }
+        }
+    }
+}
+
+/// Number of people and number of groups that answered yes to each question
+fn histogram(groups: &[Group]) -> BTreeMap<char, (usize, usize)> {
+    let mut counts = BTreeMap::new();
+    for group in groups {
+        for (c, n) in &group.answers {
+            let (people, groups) = counts.entry(*c).or_insert((0, 0));
+            *people += n;
+            *groups += 1;
+        }
+    }
+    counts
+}
+
+fn read_groups(path: &Path) -> Result<Vec<Group>> {
+    read_records(path, |section| {
+        let mut group = Group::new();
+        for line in section.lines.iter() {
+            group.add_person(line.chars());
+        }
+        Ok(group)
+    })?
+    .collect()
+}
+
+/// Run a query given as options, see `Query` for the syntax
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let query: Query = options.join(" ").parse()?;
+    query.run(&read_groups(path)?)
+}
+
+pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
+    let groups = read_groups(path)?;
     Ok((
         groups.iter().map(|g| g.num_unique_yes()).sum(),
         Some(groups.iter().map(|g| g.num_unanimous_yes()).sum()),
@@ -72,4 +261,49 @@ mod tests {
         assert_eq!(group.num_unique_yes(), 3);
         assert_eq!(group.num_unanimous_yes(), 0);
     }
+
+    fn example() -> Vec<Group> {
+        [vec!["abc", "ab", "a"], vec!["bcd", "xyz"], vec!["b"]]
+            .iter()
+            .map(|people| {
+                let mut group = Group::new();
+                for p in people {
+                    group.add_person(p.chars());
+                }
+                group
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quorum() -> Result<()> {
+        let groups = example();
+        assert_eq!(groups[0].questions("2".parse()?), "ab".chars().collect());
+        assert_eq!(groups[0].questions("50%".parse()?), "ab".chars().collect());
+        assert_eq!(groups[0].questions("34%".parse()?), "ab".chars().collect());
+        assert_eq!(groups[0].questions("33%".parse()?), "abc".chars().collect());
+        assert!("x%".parse::<Quorum>().is_err());
+        assert!("100%".parse::<Quorum>().is_ok());
+        assert!("101%".parse::<Quorum>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_queries() -> Result<()> {
+        let groups = example();
+        let run = |q: &str| q.parse::<Query>()?.run(&groups);
+        assert_eq!(run("count any")?, "10");
+        assert_eq!(run("count all")?, "2");
+        assert_eq!(run("groups 1 union 2")?, "abcdxyz");
+        assert_eq!(run("groups 1 intersect 2 minus 3")?, "c");
+        assert_eq!(run("groups 1 union 2 where all")?, "a");
+        assert!(run("groups 4").is_err());
+        assert!(run("groups 1 union").is_err());
+        assert_eq!(histogram(&groups).get(&'b'), Some(&(4, 3)));
+        assert_eq!(
+            run("histogram")?.lines().next(),
+            Some("a: 3 people in 1 groups")
+        );
+        Ok(())
+    }
 }
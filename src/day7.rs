@@ -1,61 +1,595 @@
-use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::iter;
 use std::path::Path;
+use std::str::FromStr;
 
-use crate::reader::{read_lines, split_once};
+use crate::reader::{Input, Template};
+use crate::search::bfs;
+
+static RULE_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::new("{} bags contain {}.").unwrap());
+static BAG_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::new("{} {} bag").unwrap());
+
+/// Which bags contain which other bags, and how many of them. Bags are numbered in order of
+/// first appearance. The rules can't have cycles, so every query terminates and is computed at
+/// most once per bag, in topological order so that deep nesting can't overflow the stack.
+#[derive(Debug)]
+pub struct BagGraph {
+    colors: Vec<String>,
+    index: HashMap<String, usize>,
+    /// Bags directly inside each bag with their count, in rule order
+    contents: Vec<Vec<(usize, usize)>>,
+    /// Bags that directly contain each bag
+    parents: Vec<Vec<usize>>,
+    /// Bags that have a rule, in input order
+    rules: Vec<usize>,
+    has_rule: Vec<bool>,
+    /// Every bag before the bags inside it
+    order: Vec<usize>,
+    ancestors: Vec<OnceCell<BTreeSet<usize>>>,
+    descendants: Vec<OnceCell<BTreeSet<usize>>>,
+    num_inside: Vec<OnceCell<usize>>,
+}
+
+/// Bags to include around one color when exporting part of the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subgraph {
+    Ancestors,
+    Descendants,
+    Both,
+}
+
+/// Quote a DOT identifier. Unlike `{:?}` this keeps other characters as they are, since DOT
+/// only understands escaped quotes.
+fn quote_dot(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
 
 fn parse_bag_color_with_count(bag_str: &str) -> Result<(usize, &str)> {
-    let (n_str, color_str) = split_once(bag_str, " ");
-    let color_str = color_str.ok_or_else(|| anyhow!("Invalid bag specification"))?;
-    let bag_idx = color_str
-        .rfind(" bag")
-        .ok_or_else(|| anyhow!("Bag specification must end with bag(s)"))?;
-    Ok((n_str.parse()?, &color_str[..bag_idx]))
+    let c = BAG_TEMPLATE.captures(bag_str.strip_suffix('s').unwrap_or(bag_str))?;
+    Ok((c.parse(0)?, c.get(1).unwrap()))
 }
 
-fn can_contain(bags: &HashMap<String, HashMap<String, usize>>, parent: &str, child: &str) -> bool {
-    let bag = &bags[parent];
-    for inner_bag in bag.keys() {
-        if inner_bag == child || can_contain(bags, inner_bag, child) {
-            return true;
+fn parse_rule(line: &str) -> Result<(&str, Vec<(&str, usize)>)> {
+    let c = RULE_TEMPLATE.captures(line)?;
+    let mut inner_bags = Vec::new();
+    match c.get(1).unwrap() {
+        "no other bags" => {}
+        bags_str => {
+            for inner_bag_str in bags_str.split(", ") {
+                let (n, color) = parse_bag_color_with_count(inner_bag_str)?;
+                inner_bags.push((color, n));
+            }
         }
     }
-    false
+    Ok((c.get(0).unwrap(), inner_bags))
 }
 
-fn num_bags_inside(bags: &HashMap<String, HashMap<String, usize>>, parent: &str) -> usize {
-    let mut n = bags[parent].values().sum();
-    for (inner_bag, count) in bags[parent].iter() {
-        n += count * num_bags_inside(bags, inner_bag);
+impl BagGraph {
+    fn add_color(&mut self, color: &str) -> usize {
+        if let Some(i) = self.index.get(color) {
+            return *i;
+        }
+        let i = self.colors.len();
+        self.colors.push(color.to_string());
+        self.index.insert(color.to_string(), i);
+        self.contents.push(Vec::new());
+        self.parents.push(Vec::new());
+        self.has_rule.push(false);
+        self.ancestors.push(OnceCell::new());
+        self.descendants.push(OnceCell::new());
+        self.num_inside.push(OnceCell::new());
+        i
     }
-    n
-}
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let mut bags = HashMap::new();
-    for line in read_lines(path)? {
-        let line = line?;
-        let (bag, content) = split_once(&line[..line.len() - 1], " bags contain ");
-        let inner_bags = match content {
-            Some("no other bags") => HashMap::new(),
-            Some(bags_str) => {
-                let mut inner_bags = HashMap::new();
-                for inner_bag_str in bags_str.split(", ") {
-                    let (n, color) = parse_bag_color_with_count(inner_bag_str)?;
-                    inner_bags.insert(color.to_owned(), n);
+    fn add_rule(&mut self, color: &str, contents: &[(&str, usize)]) -> Result<()> {
+        let bag = self.add_color(color);
+        if self.has_rule[bag] {
+            return Err(anyhow!("More than one rule for {:?} bags", color));
+        }
+        self.has_rule[bag] = true;
+        self.rules.push(bag);
+        for (inner, count) in contents {
+            let inner = self.add_color(inner);
+            self.contents[bag].push((inner, *count));
+            self.parents[inner].push(bag);
+        }
+        Ok(())
+    }
+
+    /// Every bag ordered before the bags inside it, or an error with one of the cycles if there
+    /// are any. Uses Kahn's algorithm, which only reaches the bags that aren't part of or inside
+    /// a cycle.
+    fn check_acyclic(&self) -> Result<Vec<usize>> {
+        let mut num_parents = self.parents.iter().map(|p| p.len()).collect::<Vec<_>>();
+        let mut queue = (0..self.colors.len())
+            .filter(|i| num_parents[*i] == 0)
+            .collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.colors.len());
+        while let Some(bag) = queue.pop() {
+            order.push(bag);
+            for (inner, _) in &self.contents[bag] {
+                num_parents[*inner] -= 1;
+                if num_parents[*inner] == 0 {
+                    queue.push(*inner);
+                }
+            }
+        }
+
+        // Every remaining bag is inside another remaining bag, so following parents from any of
+        // them must eventually repeat
+        let mut bag = match num_parents.iter().position(|n| *n > 0) {
+            Some(bag) => bag,
+            None => return Ok(order),
+        };
+        let mut path = vec![bag];
+        loop {
+            bag = *self.parents[bag]
+                .iter()
+                .find(|p| num_parents[**p] > 0)
+                .unwrap();
+            if let Some(start) = path.iter().position(|b| *b == bag) {
+                let mut cycle = path[start..].to_vec();
+                cycle.reverse();
+                // Start at the bag that appears first in the input
+                let first = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap();
+                cycle.rotate_left(first);
+                cycle.push(cycle[0]);
+                return Err(anyhow!(
+                    "Bag rules have a cycle: {}",
+                    cycle
+                        .iter()
+                        .map(|b| self.colors[*b].as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                ));
+            }
+            path.push(bag);
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Input::from_path(path)?.as_str().parse()
+    }
+
+    fn bag(&self, color: &str) -> Result<usize> {
+        self.index
+            .get(color)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown bag color {:?}", color))
+    }
+
+    fn to_colors<'a>(&'a self, bags: impl IntoIterator<Item = &'a usize>) -> Vec<&'a str> {
+        let mut colors = bags
+            .into_iter()
+            .map(|b| self.colors[*b].as_str())
+            .collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors
+    }
+
+    /// All colors, in order of first appearance
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.colors.iter().map(|c| c.as_str())
+    }
+
+    /// Bags directly inside a bag, with their count
+    pub fn contents(&self, color: &str) -> Result<Vec<(&str, usize)>> {
+        Ok(self.contents[self.bag(color)?]
+            .iter()
+            .map(|(b, n)| (self.colors[*b].as_str(), *n))
+            .collect())
+    }
+
+    /// Whether each bag is reachable from the bag through next without passing a cached bag,
+    /// including the bag itself. These are the bags that a query has to compute.
+    fn uncached<F, I>(
+        &self,
+        bag: usize,
+        mut next: F,
+        is_cached: impl Fn(usize) -> bool,
+    ) -> Vec<bool>
+    where
+        F: FnMut(usize) -> I,
+        I: IntoIterator<Item = usize>,
+    {
+        let mut needed = vec![false; self.colors.len()];
+        let mut stack = vec![bag];
+        needed[bag] = true;
+        while let Some(b) = stack.pop() {
+            for n in next(b) {
+                if !needed[n] && !is_cached(n) {
+                    needed[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        needed
+    }
+
+    fn ancestor_set(&self, bag: usize) -> &BTreeSet<usize> {
+        if let Some(ancestors) = self.ancestors[bag].get() {
+            return ancestors;
+        }
+        let needed = self.uncached(
+            bag,
+            |b| self.parents[b].iter().copied(),
+            |b| self.ancestors[b].get().is_some(),
+        );
+        // Parents come first, so their sets are always ready
+        for b in self.order.iter().copied().filter(|b| needed[*b]) {
+            let mut ancestors = BTreeSet::new();
+            for parent in &self.parents[b] {
+                ancestors.insert(*parent);
+                ancestors.extend(self.ancestors[*parent].get().unwrap());
+            }
+            let _ = self.ancestors[b].set(ancestors);
+        }
+        self.ancestors[bag].get().unwrap()
+    }
+
+    fn descendant_set(&self, bag: usize) -> &BTreeSet<usize> {
+        if let Some(descendants) = self.descendants[bag].get() {
+            return descendants;
+        }
+        let needed = self.uncached(
+            bag,
+            |b| self.contents[b].iter().map(|(inner, _)| *inner),
+            |b| self.descendants[b].get().is_some(),
+        );
+        // Inner bags come first in reverse order, so their sets are always ready
+        for b in self.order.iter().rev().copied().filter(|b| needed[*b]) {
+            let mut descendants = BTreeSet::new();
+            for (inner, _) in &self.contents[b] {
+                descendants.insert(*inner);
+                descendants.extend(self.descendants[*inner].get().unwrap());
+            }
+            let _ = self.descendants[b].set(descendants);
+        }
+        self.descendants[bag].get().unwrap()
+    }
+
+    /// Colors of every bag that can eventually contain the bag, sorted by name
+    pub fn ancestors(&self, color: &str) -> Result<Vec<&str>> {
+        Ok(self.to_colors(self.ancestor_set(self.bag(color)?)))
+    }
+
+    /// Colors of every bag that can eventually be inside the bag, sorted by name
+    pub fn descendants(&self, color: &str) -> Result<Vec<&str>> {
+        Ok(self.to_colors(self.descendant_set(self.bag(color)?)))
+    }
+
+    /// Whether the outer bag can eventually contain the inner bag. Searches the graph directly
+    /// instead of building the descendant sets, which can be huge for deeply nested bags.
+    pub fn can_contain(&self, outer: &str, inner: &str) -> Result<bool> {
+        Ok(self.containment_path(outer, inner)?.is_some())
+    }
+
+    fn num_inside_bag(&self, bag: usize) -> Result<usize> {
+        if let Some(n) = self.num_inside[bag].get() {
+            return Ok(*n);
+        }
+        let needed = self.uncached(
+            bag,
+            |b| self.contents[b].iter().map(|(inner, _)| *inner),
+            |b| self.num_inside[b].get().is_some(),
+        );
+        for b in self.order.iter().rev().copied().filter(|b| needed[*b]) {
+            let mut total = 0usize;
+            for (inner, n) in &self.contents[b] {
+                total = self.num_inside[*inner]
+                    .get()
+                    .and_then(|inside| inside.checked_add(1)?.checked_mul(*n))
+                    .and_then(|bags| total.checked_add(bags))
+                    .ok_or_else(|| {
+                        anyhow!("Too many bags inside {:?} bags to count", self.colors[b])
+                    })?;
+            }
+            let _ = self.num_inside[b].set(total);
+        }
+        Ok(*self.num_inside[bag].get().unwrap())
+    }
+
+    /// Total number of bags inside a bag, counting nested bags
+    pub fn num_bags_inside(&self, color: &str) -> Result<usize> {
+        self.num_inside_bag(self.bag(color)?)
+    }
+
+    /// Shortest chain of bags from the outer bag to the inner bag, including both, or None if the
+    /// outer bag can't contain the inner bag
+    pub fn containment_path(&self, outer: &str, inner: &str) -> Result<Option<Vec<&str>>> {
+        let (outer, inner) = (self.bag(outer)?, self.bag(inner)?);
+        let result = bfs(
+            self.contents[outer].iter().map(|(b, _)| *b),
+            |bag| {
+                self.contents[*bag]
+                    .iter()
+                    .map(|(b, _)| *b)
+                    .collect::<Vec<_>>()
+            },
+            |bag| *bag == inner,
+        );
+        Ok(result.map(|r| {
+            iter::once(outer)
+                .chain(r.path)
+                .map(|b| self.colors[b].as_str())
+                .collect()
+        }))
+    }
+
+    /// The bag and the bags around it, or every bag without a center
+    fn subgraph(&self, center: Option<(&str, Subgraph)>) -> Result<BTreeSet<usize>> {
+        let (color, subgraph) = match center {
+            Some(center) => center,
+            None => return Ok((0..self.colors.len()).collect()),
+        };
+        let bag = self.bag(color)?;
+        let mut bags = BTreeSet::new();
+        bags.insert(bag);
+        if subgraph != Subgraph::Descendants {
+            bags.extend(self.ancestor_set(bag));
+        }
+        if subgraph != Subgraph::Ancestors {
+            bags.extend(self.descendant_set(bag));
+        }
+        Ok(bags)
+    }
+
+    /// GraphViz graph with an edge from every bag to the bags directly inside it, labelled with
+    /// the count. The graph can be limited to the bags around one color, which is drawn in bold.
+    pub fn to_dot(&self, center: Option<(&str, Subgraph)>) -> Result<String> {
+        let bags = self.subgraph(center)?;
+        let center = center.map(|(color, _)| self.index[color]);
+        let mut dot = String::from("digraph bags {\n");
+        for bag in &bags {
+            if Some(*bag) == center {
+                dot += &format!("    {} [style=bold];\n", quote_dot(&self.colors[*bag]));
+            } else {
+                dot += &format!("    {};\n", quote_dot(&self.colors[*bag]));
+            }
+        }
+        for bag in &bags {
+            for (inner, n) in &self.contents[*bag] {
+                if bags.contains(inner) {
+                    dot += &format!(
+                        "    {} -> {} [label={}];\n",
+                        quote_dot(&self.colors[*bag]),
+                        quote_dot(&self.colors[*inner]),
+                        n,
+                    );
                 }
-                inner_bags
             }
-            None => {
-                return Err(anyhow!("Invalid line, must contain ' bags contain '"));
+        }
+        dot += "}\n";
+        Ok(dot)
+    }
+}
+
+impl FromStr for Subgraph {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "ancestors" => Self::Ancestors,
+            "descendants" => Self::Descendants,
+            "both" => Self::Both,
+            _ => return Err(anyhow!("Unknown subgraph {:?}", s)),
+        })
+    }
+}
+
+impl fmt::Display for BagGraph {
+    /// The rules in the same format and order as the input, one per line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bag in &self.rules {
+            write!(f, "{} bags contain ", self.colors[*bag])?;
+            if self.contents[*bag].is_empty() {
+                write!(f, "no other bags")?;
             }
+            for (i, (inner, n)) in self.contents[*bag].iter().enumerate() {
+                let plural = if *n == 1 { "" } else { "s" };
+                let separator = if i == 0 { "" } else { ", " };
+                write!(
+                    f,
+                    "{}{} {} bag{}",
+                    separator, n, self.colors[*inner], plural
+                )?;
+            }
+            writeln!(f, ".")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BagGraph {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut graph = Self {
+            colors: Vec::new(),
+            index: HashMap::new(),
+            contents: Vec::new(),
+            parents: Vec::new(),
+            rules: Vec::new(),
+            has_rule: Vec::new(),
+            order: Vec::new(),
+            ancestors: Vec::new(),
+            descendants: Vec::new(),
+            num_inside: Vec::new(),
         };
-        bags.insert(bag.to_owned(), inner_bags);
+        for (i, line) in s.lines().enumerate() {
+            let (color, contents) = parse_rule(line).with_context(|| format!("Line {}", i + 1))?;
+            graph
+                .add_rule(color, &contents)
+                .with_context(|| format!("Line {}", i + 1))?;
+        }
+        graph.order = graph.check_acyclic()?;
+        Ok(graph)
     }
+}
+
+/// Query the rules with `count <color>`, `ancestors <color>`, `descendants <color>` or
+/// `why <outer> <inner>`, export them with `dot [<color> ancestors|descendants|both]` or print
+/// them back with `print`. Colors with spaces have to be quoted.
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let graph = BagGraph::from_path(path)?;
+    match options {
+        [mode, color] if mode == "count" => Ok(graph.num_bags_inside(color)?.to_string()),
+        [mode, color] if mode == "ancestors" => Ok(graph.ancestors(color)?.join("\n")),
+        [mode, color] if mode == "descendants" => Ok(graph.descendants(color)?.join("\n")),
+        [mode, outer, inner] if mode == "why" => match graph.containment_path(outer, inner)? {
+            Some(path) => Ok(path.join(" -> ")),
+            None => Ok(format!("{} bags can't contain {} bags", outer, inner)),
+        },
+        [mode] if mode == "dot" => graph.to_dot(None),
+        [mode, color, subgraph] if mode == "dot" => {
+            graph.to_dot(Some((color, subgraph.parse()?)))
+        }
+        [mode] if mode == "print" => Ok(graph.to_string()),
+        _ => Err(anyhow!(
+            "Expected options: count <color>, ancestors <color>, descendants <color>, why <outer> <inner>, dot [<color> ancestors|descendants|both] or print"
+        )),
+    }
+}
+
+pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
+    let graph = BagGraph::from_path(path)?;
     Ok((
-        bags.keys()
-            .filter(|bag| can_contain(&bags, bag, "shiny gold"))
-            .count(),
-        Some(num_bags_inside(&bags, "shiny gold")),
+        graph.ancestors("shiny gold")?.len(),
+        Some(graph.num_bags_inside("shiny gold")?),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn test_graph() -> Result<()> {
+        let graph: BagGraph = EXAMPLE.parse()?;
+        assert_eq!(
+            graph.ancestors("shiny gold")?,
+            vec!["bright white", "dark orange", "light red", "muted yellow"],
+        );
+        assert_eq!(
+            graph.descendants("shiny gold")?,
+            vec!["dark olive", "dotted black", "faded blue", "vibrant plum"],
+        );
+        assert_eq!(graph.num_bags_inside("shiny gold")?, 32);
+        assert_eq!(graph.num_bags_inside("faded blue")?, 0);
+        assert_eq!(graph.num_bags_inside("muted yellow")?, 75);
+        assert!(graph.can_contain("light red", "faded blue")?);
+        assert!(!graph.can_contain("faded blue", "light red")?);
+        assert!(graph.ancestors("pink").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_containment_path() -> Result<()> {
+        let graph: BagGraph = EXAMPLE.parse()?;
+        assert_eq!(
+            graph.containment_path("light red", "dark olive")?,
+            Some(vec![
+                "light red",
+                "bright white",
+                "shiny gold",
+                "dark olive"
+            ]),
+        );
+        assert_eq!(graph.containment_path("shiny gold", "light red")?, None);
+        assert_eq!(graph.containment_path("shiny gold", "shiny gold")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> Result<()> {
+        let graph: BagGraph = EXAMPLE.parse()?;
+        assert_eq!(graph.to_string(), format!("{}\n", EXAMPLE));
+
+        assert_eq!(
+            graph.to_dot(Some(("dark olive", Subgraph::Descendants)))?,
+            "\
+digraph bags {
+    \"faded blue\";
+    \"dark olive\" [style=bold];
+    \"dotted black\";
+    \"dark olive\" -> \"faded blue\" [label=3];
+    \"dark olive\" -> \"dotted black\" [label=4];
+}
+",
+        );
+        let dot = graph.to_dot(Some(("bright white", Subgraph::Both)))?;
+        assert!(dot.contains("\"light red\" -> \"bright white\" [label=1];"));
+        assert!(!dot.contains("muted yellow"));
+        assert_eq!(graph.to_dot(None)?.matches(" -> ").count(), 13);
+
+        let graph: BagGraph = r#"pâle bags contain 1 vert\"clair bag."#.parse()?;
+        assert_eq!(
+            graph.to_dot(None)?,
+            r#"digraph bags {
+    "pâle";
+    "vert\\\"clair";
+    "pâle" -> "vert\\\"clair" [label=1];
+}
+"#,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules = "\
+red bags contain 1 blue bag.
+blue bags contain 2 green bags, 1 white bag.
+green bags contain 1 red bag.
+white bags contain no other bags.";
+        let error = rules.parse::<BagGraph>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Bag rules have a cycle: red -> blue -> green -> red",
+        );
+
+        let error = "red bags contain 1 red bag."
+            .parse::<BagGraph>()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Bag rules have a cycle: red -> red");
+    }
+
+    /// Rules where bag 0 contains count of bag 1, which contains count of bag 2 and so on
+    fn chain(depth: usize, count: usize) -> String {
+        let mut rules = (0..depth)
+            .map(|i| format!("c{} x bags contain {} c{} x bags.\n", i, count, i + 1))
+            .collect::<String>();
+        rules += &format!("c{} x bags contain no other bags.", depth);
+        rules
+    }
+
+    #[test]
+    fn test_deep_nesting() -> Result<()> {
+        let graph: BagGraph = chain(200_000, 1).parse()?;
+        assert_eq!(graph.num_bags_inside("c0 x")?, 200_000);
+        assert!(graph.can_contain("c0 x", "c200000 x")?);
+        assert!(graph.can_contain("c0 x", "c1 x")?);
+        assert!(!graph.can_contain("c200000 x", "c0 x")?);
+        assert_eq!(graph.ancestors("c10 x")?.len(), 10);
+
+        let graph: BagGraph = chain(10, 1000).parse()?;
+        assert_eq!(graph.num_bags_inside("c5 x")?, 1_001_001_001_001_000);
+        assert!(graph.num_bags_inside("c0 x").is_err());
+        Ok(())
+    }
+}
@@ -1,92 +1,73 @@
 use anyhow::{anyhow, Result};
 use std::collections::HashSet;
 use std::path::Path;
-use std::str::FromStr;
 
-use crate::reader::{read_parsed_lines, split_once};
+use crate::reader::Input;
+use crate::vm::{assemble, disassemble, Exit, Instruction, Machine, Op, RunMode};
 
-#[derive(Debug, Clone)]
-enum Instruction {
-    Acc(isize),
-    Jmp(isize),
-    Nop(isize),
-}
-
-#[derive(Debug)]
-enum State {
-    Halted(isize),
-    Repeated(isize),
-    Error(anyhow::Error),
-}
-
-impl Instruction {
-    fn flip(&mut self) {
-        *self = match self {
-            Instruction::Acc(i) => Instruction::Acc(*i),
-            Instruction::Jmp(i) => Instruction::Nop(*i),
-            Instruction::Nop(i) => Instruction::Jmp(*i),
-        };
-    }
+fn flip(instruction: &mut Instruction) {
+    instruction.op = match instruction.op {
+        Op::Jmp => Op::Nop,
+        Op::Nop => Op::Jmp,
+        op => op,
+    };
 }
 
-impl FromStr for Instruction {
-    type Err = anyhow::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match split_once(s, " ") {
-            ("acc", Some(i_str)) => Ok(Instruction::Acc(i_str.parse()?)),
-            ("jmp", Some(i_str)) => Ok(Instruction::Jmp(i_str.parse()?)),
-            ("nop", Some(i_str)) => Ok(Instruction::Nop(i_str.parse()?)),
-            _ => Err(anyhow!("Invalid instruction {:?}", s)),
-        }
-    }
+fn read_program(path: &Path) -> Result<Vec<Instruction>> {
+    assemble(Input::from_path(path)?.as_str())
 }
 
-fn run(program: &[Instruction]) -> State {
-    let mut prev_instrs = HashSet::new();
-    let mut ptr = 0isize;
-    let mut reg = 0isize;
-    while ptr != program.len() as isize {
-        // Check if we have visited this instruction previously
-        if prev_instrs.contains(&ptr) {
-            return State::Repeated(reg);
+/// Print every step with `trace [max_steps]`, stopping before an instruction repeats, or print
+/// the assembled program with `disassemble`
+pub fn cli(path: &Path, options: &[String]) -> Result<String> {
+    let program = read_program(path)?;
+    let max_steps = match options {
+        [mode] if mode == "disassemble" => return Ok(disassemble(&program)),
+        [mode] if mode == "trace" => usize::MAX,
+        [mode, max_steps] if mode == "trace" => max_steps.parse()?,
+        _ => {
+            return Err(anyhow!(
+                "Expected options: trace [max_steps] or disassemble"
+            ))
         }
-        prev_instrs.insert(ptr);
-
-        match program.get(ptr as usize) {
-            Some(Instruction::Nop(_)) => {
-                ptr += 1;
-            }
-            Some(Instruction::Acc(i)) => {
-                reg += i;
-                ptr += 1;
-            }
-            Some(Instruction::Jmp(i)) => {
-                ptr += i;
-            }
-            None => {
-                return State::Error(anyhow!("Current instruction ({}) is out of bounds", ptr));
-            }
+    };
+    let mut machine = Machine::new(&program);
+    let mut visited = HashSet::new();
+    let mut trace = Vec::new();
+    while trace.len() < max_steps && visited.insert(machine.ip()) {
+        let instruction = match machine.current()? {
+            Some(instruction) => instruction,
+            None => break,
         };
+        let ip = machine.ip();
+        machine.step()?;
+        trace.push(format!(
+            "{:>4}: {:<12} {:?}",
+            ip,
+            instruction.to_string(),
+            machine.registers()
+        ));
     }
-    State::Halted(reg)
+    Ok(trace.join("\n"))
 }
 
 pub fn main(path: &Path) -> Result<(isize, Option<isize>)> {
-    let mut program = read_parsed_lines(path)?.collect::<Result<Vec<Instruction>>>()?;
-    let part_a = if let State::Repeated(reg) = run(&program) {
-        reg
-    } else {
-        return Err(anyhow!("Part A is expected to run in an infinite loop"));
+    let mut program = read_program(path)?;
+    let mut machine = Machine::new(&program);
+    let part_a = match machine.run(RunMode::StopOnRepeat)? {
+        Exit::Repeated => machine.register("acc")?,
+        _ => return Err(anyhow!("Part A is expected to run in an infinite loop")),
     };
 
     let mut part_b = None;
     for i in 0..program.len() {
-        program[i].flip();
-        if let State::Halted(reg) = run(&program) {
-            part_b = Some(reg);
+        flip(&mut program[i]);
+        let mut machine = Machine::new(&program);
+        if let Ok(Exit::Halted) = machine.run(RunMode::StopOnRepeat) {
+            part_b = Some(machine.register("acc")?);
             break;
         }
-        program[i].flip();
+        flip(&mut program[i]);
     }
 
     Ok((part_a, part_b))
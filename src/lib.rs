@@ -1,5 +1,9 @@
-mod coord;
-mod reader;
+pub mod automaton;
+pub mod coord;
+pub mod reader;
+pub mod schema;
+pub mod search;
+pub mod vm;
 
 pub mod day1;
 pub mod day10;
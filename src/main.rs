@@ -23,39 +23,53 @@ fn main() -> Result<()> {
         return Err(anyhow!("Not enough arguments"));
     }
 
-    let path: Option<&Path> = if args.len() == 3 {
-        Some(Path::new(&args[2]))
-    } else {
-        None
-    };
+    let path: Option<&Path> = args.get(2).map(Path::new);
+
+    // Anything after the path selects a day specific mode
+    let options = args.get(3..).unwrap_or_default();
+    if let (Some(path), false) = (path, options.is_empty()) {
+        let output = match args[1].parse() {
+            Ok(1) => day1::cli(path, options)?,
+            Ok(2) => day2::cli(path, options)?,
+            Ok(3) => day3::cli(path, options)?,
+            Ok(4) => day4::cli(path, options)?,
+            Ok(5) => day5::cli(path, options)?,
+            Ok(6) => day6::cli(path, options)?,
+            Ok(7) => day7::cli(path, options)?,
+            Ok(8) => day8::cli(path, options)?,
+            _ => return Err(anyhow!("Day {} has no extra options", args[1])),
+        };
+        println!("{}", output);
+        return Ok(());
+    }
 
-    #[allow(overlapping_patterns, unreachable_patterns)]
+    #[allow(overlapping_range_endpoints, unreachable_patterns)]
     let result: (String, Option<String>) = match args[1].parse() {
-        Ok(1) => as_result(day1::main(path.unwrap_or(&Path::new("data/day1.txt")))?),
-        Ok(2) => as_result(day2::main(path.unwrap_or(&Path::new("data/day2.txt")))?),
-        Ok(3) => as_result(day3::main(path.unwrap_or(&Path::new("data/day3.txt")))?),
-        Ok(4) => as_result(day4::main(path.unwrap_or(&Path::new("data/day4.txt")))?),
-        Ok(5) => as_result(day5::main(path.unwrap_or(&Path::new("data/day5.txt")))?),
-        Ok(6) => as_result(day6::main(path.unwrap_or(&Path::new("data/day6.txt")))?),
-        Ok(7) => as_result(day7::main(path.unwrap_or(&Path::new("data/day7.txt")))?),
-        Ok(8) => as_result(day8::main(path.unwrap_or(&Path::new("data/day8.txt")))?),
-        Ok(9) => as_result(day9::main(path.unwrap_or(&Path::new("data/day9.txt")))?),
-        Ok(10) => as_result(day10::main(path.unwrap_or(&Path::new("data/day10.txt")))?),
-        Ok(11) => as_result(day11::main(path.unwrap_or(&Path::new("data/day11.txt")))?),
-        Ok(12) => as_result(day12::main(path.unwrap_or(&Path::new("data/day12.txt")))?),
-        Ok(13) => as_result(day13::main(path.unwrap_or(&Path::new("data/day13.txt")))?),
-        Ok(14) => as_result(day14::main(path.unwrap_or(&Path::new("data/day14.txt")))?),
-        Ok(15) => as_result(day15::main(path.unwrap_or(&Path::new("data/day15.txt")))?),
-        Ok(16) => as_result(day16::main(path.unwrap_or(&Path::new("data/day16.txt")))?),
-        Ok(17) => as_result(day17::main(path.unwrap_or(&Path::new("data/day17.txt")))?),
-        Ok(18) => as_result(day18::main(path.unwrap_or(&Path::new("data/day18.txt")))?),
-        Ok(19) => as_result(day19::main(path.unwrap_or(&Path::new("data/day19.txt")))?),
-        Ok(20) => as_result(day20::main(path.unwrap_or(&Path::new("data/day20.txt")))?),
-        Ok(21) => as_result(day21::main(path.unwrap_or(&Path::new("data/day21.txt")))?),
-        Ok(22) => as_result(day22::main(path.unwrap_or(&Path::new("data/day22.txt")))?),
-        Ok(23) => as_result(day23::main(path.unwrap_or(&Path::new("data/day23.txt")))?),
-        Ok(24) => as_result(day24::main(path.unwrap_or(&Path::new("data/day24.txt")))?),
-        Ok(25) => as_result(day25::main(path.unwrap_or(&Path::new("data/day25.txt")))?),
+        Ok(1) => as_result(day1::main(path.unwrap_or(Path::new("data/day1.txt")))?),
+        Ok(2) => as_result(day2::main(path.unwrap_or(Path::new("data/day2.txt")))?),
+        Ok(3) => as_result(day3::main(path.unwrap_or(Path::new("data/day3.txt")))?),
+        Ok(4) => as_result(day4::main(path.unwrap_or(Path::new("data/day4.txt")))?),
+        Ok(5) => as_result(day5::main(path.unwrap_or(Path::new("data/day5.txt")))?),
+        Ok(6) => as_result(day6::main(path.unwrap_or(Path::new("data/day6.txt")))?),
+        Ok(7) => as_result(day7::main(path.unwrap_or(Path::new("data/day7.txt")))?),
+        Ok(8) => as_result(day8::main(path.unwrap_or(Path::new("data/day8.txt")))?),
+        Ok(9) => as_result(day9::main(path.unwrap_or(Path::new("data/day9.txt")))?),
+        Ok(10) => as_result(day10::main(path.unwrap_or(Path::new("data/day10.txt")))?),
+        Ok(11) => as_result(day11::main(path.unwrap_or(Path::new("data/day11.txt")))?),
+        Ok(12) => as_result(day12::main(path.unwrap_or(Path::new("data/day12.txt")))?),
+        Ok(13) => as_result(day13::main(path.unwrap_or(Path::new("data/day13.txt")))?),
+        Ok(14) => as_result(day14::main(path.unwrap_or(Path::new("data/day14.txt")))?),
+        Ok(15) => as_result(day15::main(path.unwrap_or(Path::new("data/day15.txt")))?),
+        Ok(16) => as_result(day16::main(path.unwrap_or(Path::new("data/day16.txt")))?),
+        Ok(17) => as_result(day17::main(path.unwrap_or(Path::new("data/day17.txt")))?),
+        Ok(18) => as_result(day18::main(path.unwrap_or(Path::new("data/day18.txt")))?),
+        Ok(19) => as_result(day19::main(path.unwrap_or(Path::new("data/day19.txt")))?),
+        Ok(20) => as_result(day20::main(path.unwrap_or(Path::new("data/day20.txt")))?),
+        Ok(21) => as_result(day21::main(path.unwrap_or(Path::new("data/day21.txt")))?),
+        Ok(22) => as_result(day22::main(path.unwrap_or(Path::new("data/day22.txt")))?),
+        Ok(23) => as_result(day23::main(path.unwrap_or(Path::new("data/day23.txt")))?),
+        Ok(24) => as_result(day24::main(path.unwrap_or(Path::new("data/day24.txt")))?),
+        Ok(25) => as_result(day25::main(path.unwrap_or(Path::new("data/day25.txt")))?),
         Ok(1..=25) => return Err(anyhow!("No implementation for this day yet")),
         Ok(day) => return Err(anyhow!("Day {} is not a valid day for advent of code", day)),
         Err(_) => return Err(anyhow!("{:?} is not a valid day", args[1])),
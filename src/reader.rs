@@ -1,18 +1,137 @@
-use anyhow::Result;
-use std::collections::HashMap;
-use std::convert::TryInto;
+pub mod regions;
+
+use anyhow::{anyhow, Result};
+use std::convert::{Infallible, TryFrom};
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read};
 use std::path::Path;
 use std::str::FromStr;
 
-use crate::coord::Coord;
+use crate::coord::{Coord, Direction, Rect};
 
+/// Dense rectangular grid of tiles stored row by row
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Grid {
+pub struct Grid<T = char> {
     pub width: usize,
     pub height: usize,
-    pub tiles: HashMap<Coord, char>,
+    pub tiles: Vec<T>,
+}
+
+/// Entire input file loaded into a single buffer. Lines and records are handed out as slices of
+/// the buffer which avoids allocating for every line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    data: String,
+}
+
+/// Compiled scanf-like line template. Each `{}` or `{name}` in the template matches a field and
+/// everything else must match literally. Use `{{` and `}}` for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Field(Option<String>),
+}
+
+/// Fields extracted from a string by a `Template`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'t, 's> {
+    fields: Vec<Capture<'t, 's>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Capture<'t, 's> {
+    name: Option<&'t str>,
+    value: &'s str,
+    column: usize,
+}
+
+/// Types that can be built from all fields of a template match, like tuples of `FromStr` types
+pub trait FromCaptures: Sized {
+    fn from_captures(captures: &Captures) -> Result<Self>;
+}
+
+/// One of the eight symmetries of a rectangle (rotations and reflections). Transforms are applied
+/// by first transposing, then mirroring each row and finally flipping the order of the rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Transform {
+    pub transpose: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Zero-copy transformed view of a grid
+#[derive(Clone, Copy, Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    transform: Transform,
+}
+
+/// Tiles that can be rendered as a single character, usually the inverse of `TryFrom<char>`
+pub trait ToChar {
+    fn to_char(&self) -> char;
+}
+
+/// How lookups outside of a grid behave along one axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge<T> {
+    /// There is nothing outside the grid
+    Bounded,
+    /// The grid repeats forever
+    Wrap,
+    /// Use the closest tile within the grid
+    Clamp,
+    /// Everything outside the grid is the given tile
+    Fill(T),
+}
+
+/// View of a grid that follows the given edge behavior for lookups outside of it
+#[derive(Clone, Copy, Debug)]
+pub struct EdgeView<'a, T> {
+    grid: &'a Grid<T>,
+    x_edge: Edge<T>,
+    y_edge: Edge<T>,
+}
+
+/// First tile visible from every tile of a grid in each of a set of directions, computed once
+/// by `Grid::visibility`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visibility {
+    visible: Grid<Vec<Coord>>,
+}
+
+/// A group of consecutive non-blank lines in the input. Lines are owned when reading a file line
+/// by line and borrowed when splitting an `Input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section<L = String> {
+    /// Line number (starting from 1) of the first line in the section
+    pub line_no: usize,
+    pub lines: Vec<L>,
+}
+
+/// Iterator over blank line separated sections of an iterator of lines
+#[derive(Debug)]
+pub struct Sections<I> {
+    lines: I,
+    line_no: usize,
+}
+
+/// Types that can be parsed from a single section
+pub trait FromSection: Sized {
+    fn from_section(section: &Section) -> Result<Self>;
+}
+
+/// Types that can be parsed from a fixed number of sections, like a tuple of `FromSection` types
+pub trait FromSections: Sized {
+    fn from_sections<I, E>(sections: I) -> Result<Self>
+    where
+        I: Iterator<Item = Result<Section, E>>,
+        anyhow::Error: From<E>;
 }
 
 pub fn read_lines<P>(filename: P) -> Result<io::Lines<io::BufReader<File>>>
@@ -30,8 +149,10 @@ where
     anyhow::Error: From<E>,
 {
     Ok(read_lines(path)?
-        .into_iter()
-        .map(move |l| -> Result<T> { Ok(f(&l?)?) }))
+        .enumerate()
+        .map(move |(i, l)| -> Result<T> {
+            f(&l?).map_err(|e| anyhow::Error::from(e).context(format!("Line {}", i + 1)))
+        }))
 }
 
 pub fn read_parsed_lines<P, T>(path: P) -> Result<impl Iterator<Item = Result<T>>>
@@ -43,6 +164,40 @@ where
     read_mapped_lines(path, T::from_str)
 }
 
+/// Read the file as groups of lines separated by one or more blank lines
+pub fn read_sections<P>(path: P) -> Result<Sections<io::Lines<io::BufReader<File>>>>
+where
+    P: AsRef<Path>,
+{
+    Ok(Sections::new(read_lines(path)?))
+}
+
+/// Split the string into groups of lines separated by one or more blank lines
+pub fn parse_sections(s: &str) -> Sections<impl Iterator<Item = io::Result<String>> + '_> {
+    Sections::new(s.lines().map(|l| Ok(l.to_owned())))
+}
+
+/// Read the file as blank line separated records and parse each of them using the given
+/// function. Errors are annotated with the line number the record starts at.
+pub fn read_records<P, F, T>(path: P, f: F) -> Result<impl Iterator<Item = Result<T>>>
+where
+    P: AsRef<Path>,
+    F: 'static + Fn(&Section) -> Result<T>,
+    T: 'static,
+{
+    Ok(read_sections(path)?.map(move |s| s?.parse_with(&f)))
+}
+
+/// Read a file consisting of a fixed number of sections of different types, for example
+/// `(Section, Section)` for a file with two sections
+pub fn read_typed_sections<P, T>(path: P) -> Result<T>
+where
+    P: AsRef<Path>,
+    T: FromSections,
+{
+    T::from_sections(read_sections(path)?)
+}
+
 /// Split the string at the given separator. If the separator is not found, the
 /// second part of the tuple will be None.
 pub fn split_once<'a>(s: &'a str, pat: &str) -> (&'a str, Option<&'a str>) {
@@ -53,25 +208,1289 @@ pub fn split_once<'a>(s: &'a str, pat: &str) -> (&'a str, Option<&'a str>) {
     }
 }
 
-impl Grid {
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Grid> {
-        let mut width = 0;
+impl<L: AsRef<str>> Section<L> {
+    pub fn new(line_no: usize) -> Self {
+        Self {
+            line_no,
+            lines: Vec::new(),
+        }
+    }
+
+    /// The first line of the section, often used as a title
+    pub fn header(&self) -> &str {
+        self.lines.first().map(L::as_ref).unwrap_or("")
+    }
+
+    /// All lines after the header
+    pub fn body(&self) -> &[L] {
+        self.lines.get(1..).unwrap_or(&[])
+    }
+
+    /// Parse the section using the given function and annotate any error with the line number
+    /// the section starts at
+    pub fn parse_with<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        f(self).map_err(|e| e.context(format!("Section starting at line {}", self.line_no)))
+    }
+}
+
+impl FromSection for Section {
+    fn from_section(section: &Section) -> Result<Self> {
+        Ok(section.clone())
+    }
+}
+
+impl<I> Sections<I> {
+    pub fn new(lines: I) -> Self {
+        Self { lines, line_no: 0 }
+    }
+}
+
+impl<I, L, E> Iterator for Sections<I>
+where
+    I: Iterator<Item = Result<L, E>>,
+    L: AsRef<str>,
+{
+    type Item = Result<Section<L>, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut section: Option<Section<L>> = None;
+        for line in &mut self.lines {
+            self.line_no += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if line.as_ref().trim().is_empty() {
+                if section.is_some() {
+                    break;
+                }
+                continue;
+            }
+
+            let line_no = self.line_no;
+            section
+                .get_or_insert_with(|| Section::new(line_no))
+                .lines
+                .push(line);
+        }
+        section.map(Ok)
+    }
+}
+
+macro_rules! from_sections_tuple {
+    ($n:expr; $($t:ident),+) => {
+        impl<$($t: FromSection),+> FromSections for ($($t,)+) {
+            fn from_sections<I, E>(sections: I) -> Result<Self>
+            where
+                I: Iterator<Item = Result<Section, E>>,
+                anyhow::Error: From<E>,
+            {
+                let sections = sections.collect::<Result<Vec<_>, E>>()?;
+                if sections.len() != $n {
+                    return Err(anyhow!("Expected {} sections, found {}", $n, sections.len()));
+                }
+
+                let mut sections = sections.iter();
+                Ok(($(sections.next().unwrap().parse_with($t::from_section)?,)+))
+            }
+        }
+    };
+}
+
+from_sections_tuple!(1; A);
+from_sections_tuple!(2; A, B);
+from_sections_tuple!(3; A, B, C);
+from_sections_tuple!(4; A, B, C, D);
+
+impl Input {
+    /// Read the whole file with a single allocation sized after the file's metadata
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+        let mut buf = Vec::with_capacity(len + 1);
+        file.read_to_end(&mut buf)?;
+        Ok(Self::from(String::from_utf8(buf)?))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.data
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.as_str().lines()
+    }
+
+    /// Lines as raw bytes, without the trailing line break
+    pub fn byte_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines().map(str::as_bytes)
+    }
+
+    /// Blank line separated groups of lines borrowed from the input
+    pub fn records(&self) -> impl Iterator<Item = Section<&str>> {
+        Sections::new(self.lines().map(Ok::<_, Infallible>)).map(|s| match s {
+            Ok(section) => section,
+            Err(e) => match e {},
+        })
+    }
+
+    /// Parse every line using the given function. Errors are annotated with the line number.
+    pub fn parse_lines<'a, F, T>(&'a self, f: F) -> impl Iterator<Item = Result<T>> + 'a
+    where
+        F: 'a + Fn(&'a str) -> Result<T>,
+    {
+        self.lines()
+            .enumerate()
+            .map(move |(i, l)| f(l).map_err(|e| e.context(format!("Line {}", i + 1))))
+    }
+}
+
+impl From<String> for Input {
+    fn from(data: String) -> Self {
+        Self { data }
+    }
+}
+
+/// Column (starting from 1) of the given byte offset in the string
+fn column(s: &str, offset: usize) -> usize {
+    s[..offset].chars().count() + 1
+}
+
+impl Template {
+    pub fn new(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let (name, rest) = split_once(chars.as_str(), "}");
+                    let rest = rest.ok_or_else(|| anyhow!("Unclosed field in {:?}", template))?;
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    } else if let Some(TemplatePart::Field(_)) = parts.last() {
+                        return Err(anyhow!(
+                            "Fields must be separated by a literal in {:?}",
+                            template,
+                        ));
+                    }
+                    parts.push(TemplatePart::Field(if name.is_empty() {
+                        None
+                    } else {
+                        Some(name.to_owned())
+                    }));
+                    chars = rest.chars();
+                }
+                '}' => return Err(anyhow!("Unmatched '}}' in {:?}", template)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn num_fields(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|p| matches!(p, TemplatePart::Field(_)))
+            .count()
+    }
+
+    /// Match the string against the template. Fields extend until the first occurrence of the
+    /// literal that follows them, except for the last literal which must end the string.
+    pub fn captures<'t, 's>(&'t self, s: &'s str) -> Result<Captures<'t, 's>> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        let mut i = 0;
+        while i < self.parts.len() {
+            match &self.parts[i] {
+                TemplatePart::Literal(lit) => {
+                    if !s[pos..].starts_with(lit.as_str()) {
+                        return Err(anyhow!("Column {}: expected {:?}", column(s, pos), lit));
+                    }
+                    pos += lit.len();
+                }
+                TemplatePart::Field(name) => {
+                    let end = match self.parts.get(i + 1) {
+                        Some(TemplatePart::Literal(lit)) if i + 2 == self.parts.len() => {
+                            if s.len() < pos + lit.len() || !s.ends_with(lit.as_str()) {
+                                return Err(anyhow!(
+                                    "Column {}: expected line to end with {:?}",
+                                    column(s, pos),
+                                    lit,
+                                ));
+                            }
+                            s.len() - lit.len()
+                        }
+                        Some(TemplatePart::Literal(lit)) => {
+                            pos + s[pos..].find(lit.as_str()).ok_or_else(|| {
+                                anyhow!("Column {}: expected {:?}", column(s, pos), lit)
+                            })?
+                        }
+                        _ => s.len(),
+                    };
+                    fields.push(Capture {
+                        name: name.as_deref(),
+                        value: &s[pos..end],
+                        column: column(s, pos),
+                    });
+                    pos = end;
+                }
+            }
+            i += 1;
+        }
+
+        if pos != s.len() {
+            return Err(anyhow!(
+                "Column {}: unexpected trailing {:?}",
+                column(s, pos),
+                &s[pos..],
+            ));
+        }
+        Ok(Captures { fields })
+    }
+
+    /// Match the string and parse all fields, typically into a tuple
+    pub fn scan<T: FromCaptures>(&self, s: &str) -> Result<T> {
+        T::from_captures(&self.captures(s)?)
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl<'t, 's> Captures<'t, 's> {
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&'s str> {
+        self.fields.get(i).map(|f| f.value)
+    }
+
+    pub fn name(&self, name: &str) -> Option<&'s str> {
+        self.fields
+            .iter()
+            .find(|f| f.name == Some(name))
+            .map(|f| f.value)
+    }
+
+    fn parse_field<T>(field: &Capture) -> Result<T>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        field.value.parse().map_err(|e| {
+            anyhow::Error::from(e).context(format!(
+                "Column {}: invalid value {:?}",
+                field.column, field.value,
+            ))
+        })
+    }
+
+    /// Parse the field at the given position
+    pub fn parse<T>(&self, i: usize) -> Result<T>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        let field = self
+            .fields
+            .get(i)
+            .ok_or_else(|| anyhow!("No field at position {}", i))?;
+        Self::parse_field(field)
+    }
+
+    /// Parse the field with the given name
+    pub fn parse_name<T>(&self, name: &str) -> Result<T>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        let field = self
+            .fields
+            .iter()
+            .find(|f| f.name == Some(name))
+            .ok_or_else(|| anyhow!("No field named {:?}", name))?;
+        Self::parse_field(field)
+    }
+}
+
+macro_rules! from_captures_tuple {
+    ($n:expr; $($i:tt: $t:ident),+) => {
+        impl<$($t),+> FromCaptures for ($($t,)+)
+        where
+            $($t: FromStr, anyhow::Error: From<$t::Err>,)+
+        {
+            fn from_captures(captures: &Captures) -> Result<Self> {
+                if captures.len() != $n {
+                    return Err(anyhow!("Expected {} fields, found {}", $n, captures.len()));
+                }
+                Ok(($(captures.parse::<$t>($i)?,)+))
+            }
+        }
+    };
+}
+
+from_captures_tuple!(1; 0: A);
+from_captures_tuple!(2; 0: A, 1: B);
+from_captures_tuple!(3; 0: A, 1: B, 2: C);
+from_captures_tuple!(4; 0: A, 1: B, 2: C, 3: D);
+from_captures_tuple!(5; 0: A, 1: B, 2: C, 3: D, 4: E);
+from_captures_tuple!(6; 0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+
+impl Transform {
+    pub fn identity() -> Self {
+        Default::default()
+    }
+
+    /// Iterate over all eight transforms, starting with the identity
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..8).map(|i| Self {
+            transpose: (i >> 2) & 1 == 1,
+            flip_x: (i >> 1) & 1 == 1,
+            flip_y: i & 1 == 1,
+        })
+    }
+
+    /// Rotation 90 degrees clockwise
+    pub fn rotate_right() -> Self {
+        Self {
+            transpose: true,
+            flip_x: true,
+            flip_y: false,
+        }
+    }
+
+    /// Rotation 90 degrees counter-clockwise
+    pub fn rotate_left() -> Self {
+        Self {
+            transpose: true,
+            flip_x: false,
+            flip_y: true,
+        }
+    }
+
+    pub fn rotate_half() -> Self {
+        Self {
+            transpose: false,
+            flip_x: true,
+            flip_y: true,
+        }
+    }
+
+    /// Mirror left to right
+    pub fn flip_x() -> Self {
+        Self {
+            flip_x: true,
+            ..Default::default()
+        }
+    }
+
+    /// Mirror top to bottom
+    pub fn flip_y() -> Self {
+        Self {
+            flip_y: true,
+            ..Default::default()
+        }
+    }
+
+    /// Size of the output when applied to a grid of the given size
+    pub fn size(&self, width: usize, height: usize) -> (usize, usize) {
+        if self.transpose {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Map a coordinate in the transformed grid back to the source grid of the given size
+    pub fn source_coord(&self, c: &Coord, width: usize, height: usize) -> Coord {
+        let (out_width, out_height) = self.size(width, height);
+        let x = if self.flip_x {
+            out_width as isize - 1 - c.x
+        } else {
+            c.x
+        };
+        let y = if self.flip_y {
+            out_height as isize - 1 - c.y
+        } else {
+            c.y
+        };
+        if self.transpose {
+            Coord::new(y, x)
+        } else {
+            Coord::new(x, y)
+        }
+    }
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn new(grid: &'a Grid<T>, transform: Transform) -> Self {
+        Self { grid, transform }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn width(&self) -> usize {
+        self.transform.size(self.grid.width, self.grid.height).0
+    }
+
+    pub fn height(&self) -> usize {
+        self.transform.size(self.grid.width, self.grid.height).1
+    }
+
+    pub fn get(&self, c: &Coord) -> Option<&'a T> {
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        if c.x < 0 || c.y < 0 || c.x >= width || c.y >= height {
+            return None;
+        }
+        self.grid.get(
+            &self
+                .transform
+                .source_coord(c, self.grid.width, self.grid.height),
+        )
+    }
+
+    /// Iterate over all tiles together with their coordinate in the view, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &'a T)> + '_ {
+        let width = self.width();
+        (0..width * self.height()).map(move |i| {
+            let c = Coord::new((i % width) as isize, (i / width) as isize);
+            (c, self.get(&c).unwrap())
+        })
+    }
+
+    /// Copy the view into a new grid
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.width(),
+            height: self.height(),
+            tiles: self.iter().map(|(_, t)| t.clone()).collect(),
+        }
+    }
+}
+
+impl ToChar for char {
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+impl ToChar for bool {
+    fn to_char(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+enum AxisLookup<'a, T> {
+    Index(isize),
+    Fill(&'a T),
+    Outside,
+}
+
+impl<T> Edge<T> {
+    fn lookup(&self, v: isize, len: usize) -> AxisLookup<'_, T> {
+        let len = len as isize;
+        if v >= 0 && v < len {
+            return AxisLookup::Index(v);
+        }
+        match self {
+            Self::Fill(t) => AxisLookup::Fill(t),
+            _ if len == 0 => AxisLookup::Outside,
+            Self::Bounded => AxisLookup::Outside,
+            Self::Wrap => AxisLookup::Index(v.rem_euclid(len)),
+            Self::Clamp => AxisLookup::Index(v.clamp(0, len - 1)),
+        }
+    }
+}
+
+impl<'a, T> EdgeView<'a, T> {
+    pub fn new(grid: &'a Grid<T>, x_edge: Edge<T>, y_edge: Edge<T>) -> Self {
+        Self {
+            grid,
+            x_edge,
+            y_edge,
+        }
+    }
+
+    pub fn grid(&self) -> &'a Grid<T> {
+        self.grid
+    }
+
+    pub fn get(&self, c: &Coord) -> Option<&T> {
+        let x = self.x_edge.lookup(c.x, self.grid.width);
+        let y = self.y_edge.lookup(c.y, self.grid.height);
+        match (x, y) {
+            (AxisLookup::Outside, _) | (_, AxisLookup::Outside) => None,
+            (AxisLookup::Fill(t), _) | (_, AxisLookup::Fill(t)) => Some(t),
+            (AxisLookup::Index(x), AxisLookup::Index(y)) => self.grid.get(&Coord::new(x, y)),
+        }
+    }
+
+    /// Iterate over the tiles up, right, down and left of the given coordinate
+    pub fn neighbors(&self, c: &Coord) -> impl Iterator<Item = (Coord, &T)> {
+        c.iter_neighbors()
+            .filter_map(move |n| Some((n, self.get(&n)?)))
+    }
+
+    /// Like neighbors, but includes diagonals
+    pub fn all_neighbors(&self, c: &Coord) -> impl Iterator<Item = (Coord, &T)> {
+        c.iter_all_neighbors()
+            .filter_map(move |n| Some((n, self.get(&n)?)))
+    }
+}
+
+impl Visibility {
+    /// Tiles visible from the given coordinate, at most one per direction
+    pub fn get(&self, c: &Coord) -> &[Coord] {
+        self.visible.get(c).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Iterate over every coordinate with the tiles visible from it
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &[Coord])> {
+        self.visible.iter().map(|(c, v)| (c, v.as_slice()))
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, tiles: Vec<T>) -> Result<Self> {
+        if width * height != tiles.len() {
+            return Err(anyhow!(
+                "Grid of size {}x{} can't hold {} tiles",
+                width,
+                height,
+                tiles.len(),
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            tiles,
+        })
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(Coord) -> T,
+    {
+        let tiles = (0..width * height)
+            .map(|i| f(Coord::new((i % width) as isize, (i / width) as isize)))
+            .collect();
+        Self {
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    /// Parse a grid from lines of text where each character is a tile. All lines must have the
+    /// same length.
+    pub fn from_lines<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        T: TryFrom<char>,
+        anyhow::Error: From<T::Error>,
+    {
+        let mut width = None;
         let mut height = 0;
-        let mut tiles = HashMap::new();
-
-        for (y, line) in read_lines(path)?.enumerate() {
-            // NOTE: This doesn't check if lines have different length
-            width = 0;
-            for (x, c) in line?.chars().enumerate() {
-                tiles.insert(Coord::new(x.try_into()?, y.try_into()?), c);
-                width += 1;
+        let mut tiles = Vec::new();
+
+        for (y, line) in lines.into_iter().enumerate() {
+            let mut line_width = 0;
+            for (x, c) in line.as_ref().chars().enumerate() {
+                let tile = T::try_from(c).map_err(|e| {
+                    anyhow::Error::from(e).context(format!("Line {}, column {}", y + 1, x + 1))
+                })?;
+                tiles.push(tile);
+                line_width += 1;
+            }
+
+            match width {
+                Some(w) if w != line_width => {
+                    return Err(anyhow!(
+                        "Line {} has length {}, expected {}",
+                        y + 1,
+                        line_width,
+                        w,
+                    ));
+                }
+                Some(_) => {}
+                None => width = Some(line_width),
             }
             height += 1;
         }
+
         Ok(Self {
-            width,
+            width: width.unwrap_or(0),
             height,
             tiles,
         })
     }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self>
+    where
+        T: TryFrom<char>,
+        anyhow::Error: From<T::Error>,
+    {
+        Self::from_lines(read_lines(path)?.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.iter().map(f).collect(),
+        }
+    }
+
+    /// View of the grid with the given edge behavior for each axis
+    pub fn with_edges(&self, x_edge: Edge<T>, y_edge: Edge<T>) -> EdgeView<'_, T> {
+        EdgeView::new(self, x_edge, y_edge)
+    }
+
+    /// View of the grid where it repeats forever in both directions, like a torus
+    pub fn wrapping(&self) -> EdgeView<'_, T> {
+        self.with_edges(Edge::Wrap, Edge::Wrap)
+    }
+
+    pub fn in_bounds(&self, c: &Coord) -> bool {
+        c.x >= 0 && c.y >= 0 && c.x < self.width as isize && c.y < self.height as isize
+    }
+
+    /// Rectangle covering every tile of the grid
+    pub fn bounds(&self) -> Rect {
+        Rect::from_size(Coord::origin(), self.width, self.height)
+    }
+
+    fn coord_to_index(&self, c: &Coord) -> Option<usize> {
+        if !self.in_bounds(c) {
+            return None;
+        }
+        Some(c.y as usize * self.width + c.x as usize)
+    }
+
+    fn index_to_coord(&self, i: usize) -> Coord {
+        Coord::new((i % self.width) as isize, (i / self.width) as isize)
+    }
+
+    pub fn get(&self, c: &Coord) -> Option<&T> {
+        self.tiles.get(self.coord_to_index(c)?)
+    }
+
+    pub fn get_mut(&mut self, c: &Coord) -> Option<&mut T> {
+        let i = self.coord_to_index(c)?;
+        self.tiles.get_mut(i)
+    }
+
+    /// Iterate over all coordinates in the grid, row by row
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.width * self.height)
+            .map(move |i| Coord::new((i % width) as isize, (i / width) as isize))
+    }
+
+    /// Iterate over all tiles together with their coordinate, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(i, t)| (self.index_to_coord(i), t))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y >= self.height {
+            return None;
+        }
+        Some(&self.tiles[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| &self.tiles[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        let start = if x < width { x } else { self.tiles.len() };
+        self.tiles.iter().skip(start).step_by(width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Iterate over the tiles up, right, down and left of the given coordinate that are within
+    /// the grid
+    pub fn neighbors(&self, c: &Coord) -> impl Iterator<Item = (Coord, &T)> {
+        c.iter_neighbors()
+            .filter_map(move |n| Some((n, self.get(&n)?)))
+    }
+
+    /// Like neighbors, but includes diagonals
+    pub fn all_neighbors(&self, c: &Coord) -> impl Iterator<Item = (Coord, &T)> {
+        c.iter_all_neighbors()
+            .filter_map(move |n| Some((n, self.get(&n)?)))
+    }
+
+    /// Iterate over the tiles along a ray from the given coordinate, excluding the start, until
+    /// it leaves the grid. Each step moves by the direction's length, and a direction with zero
+    /// length gives an empty ray.
+    pub fn ray(&self, start: &Coord, dir: Direction) -> impl Iterator<Item = (Coord, &T)> {
+        let step = dir.to_coord();
+        let mut c = *start;
+        std::iter::from_fn(move || {
+            if step == Coord::origin() {
+                return None;
+            }
+            c += step;
+            Some((c, self.get(&c)?))
+        })
+    }
+
+    /// First tile along a ray from the given coordinate where stop returns true
+    pub fn cast_ray<F>(&self, start: &Coord, dir: Direction, mut stop: F) -> Option<(Coord, &T)>
+    where
+        F: FnMut(&Coord, &T) -> bool,
+    {
+        self.ray(start, dir).find(|(c, t)| stop(c, t))
+    }
+
+    /// Cast a ray in every direction from every tile and record the first tile where stop
+    /// returns true. Useful when the grid's layout doesn't change but its contents do.
+    pub fn visibility<I, F>(&self, dirs: I, mut stop: F) -> Visibility
+    where
+        I: IntoIterator<Item = Direction>,
+        F: FnMut(&Coord, &T) -> bool,
+    {
+        let dirs = dirs.into_iter().collect::<Vec<_>>();
+        let visible = Grid::from_fn(self.width, self.height, |c| {
+            dirs.iter()
+                .filter_map(|d| self.cast_ray(&c, *d, &mut stop).map(|(v, _)| v))
+                .collect()
+        });
+        Visibility { visible }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Render the grid using the given function to turn tiles into characters
+    pub fn render_with<F>(&self, f: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&f));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Coordinates where the two grids differ. Coordinates that are only within one of the grids
+    /// are included as well.
+    pub fn differences(&self, other: &Self) -> Vec<Coord>
+    where
+        T: PartialEq,
+    {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        (0..width * height)
+            .map(|i| Coord::new((i % width) as isize, (i / width) as isize))
+            .filter(|c| self.get(c) != other.get(c))
+            .collect()
+    }
+
+    /// Render the two grids side by side followed by a column where differing tiles are marked
+    /// with `X`
+    pub fn render_diff(&self, other: &Self) -> String
+    where
+        T: PartialEq + ToChar,
+    {
+        let differences = self.differences(other);
+        let height = self.height.max(other.height);
+        let render_row = |grid: &Self, y: usize| -> String {
+            (0..grid.width)
+                .map(|x| match grid.get(&Coord::new(x as isize, y as isize)) {
+                    Some(t) => t.to_char(),
+                    None => ' ',
+                })
+                .collect()
+        };
+
+        let mut out = format!(
+            "{} differences ({}x{} vs {}x{})\n",
+            differences.len(),
+            self.width,
+            self.height,
+            other.width,
+            other.height,
+        );
+        for y in 0..height {
+            let marker = (0..self.width.max(other.width))
+                .map(|x| {
+                    if differences.contains(&Coord::new(x as isize, y as isize)) {
+                        'X'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>();
+            let line = format!(
+                "{:w1$} | {:w2$} | {}",
+                render_row(self, y),
+                render_row(other, y),
+                marker,
+                w1 = self.width,
+                w2 = other.width,
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T: ToChar> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render_with(T::to_char))
+    }
+}
+
+/// Assert that two grids are equal. On failure the grids are shown side by side with the
+/// differing tiles marked.
+#[macro_export]
+macro_rules! assert_grid_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    panic!(
+                        "assertion failed: grids are not equal (left | right | diff)\n{}",
+                        left.render_diff(right),
+                    );
+                }
+            }
+        }
+    };
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn view(&self, transform: Transform) -> GridView<'_, T> {
+        GridView::new(self, transform)
+    }
+
+    /// Iterate over views of all eight rotations and reflections of the grid
+    pub fn all_views(&self) -> impl Iterator<Item = GridView<'_, T>> {
+        Transform::all().map(move |t| self.view(t))
+    }
+
+    pub fn transformed(&self, transform: Transform) -> Self {
+        self.view(transform).to_grid()
+    }
+
+    /// Return copies of all eight rotations and reflections of the grid
+    pub fn all_transforms(&self) -> Vec<Self> {
+        self.all_views().map(|v| v.to_grid()).collect()
+    }
+
+    /// Copy the rectangle of the given size with its top left corner at the given coordinate
+    pub fn sub_grid(&self, origin: &Coord, width: usize, height: usize) -> Result<Self> {
+        let end = Coord::new(
+            origin.x + width as isize - 1,
+            origin.y + height as isize - 1,
+        );
+        if width > 0 && height > 0 && !(self.in_bounds(origin) && self.in_bounds(&end)) {
+            return Err(anyhow!(
+                "Sub grid {}x{} at {:?} is outside of the {}x{} grid",
+                width,
+                height,
+                origin,
+                self.width,
+                self.height,
+            ));
+        }
+        Ok(Self::from_fn(width, height, |c| {
+            self.get(&(c + origin)).unwrap().clone()
+        }))
+    }
+
+    /// Remove the given number of tiles from every side of the grid
+    pub fn crop(&self, margin: usize) -> Result<Self> {
+        if 2 * margin > self.width || 2 * margin > self.height {
+            return Err(anyhow!(
+                "Can't crop {} tiles from each side of a {}x{} grid",
+                margin,
+                self.width,
+                self.height,
+            ));
+        }
+        self.sub_grid(
+            &Coord::new(margin as isize, margin as isize),
+            self.width - 2 * margin,
+            self.height - 2 * margin,
+        )
+    }
+
+    /// Top row, left to right
+    pub fn top_border(&self) -> Vec<T> {
+        self.row(0).map(<[T]>::to_vec).unwrap_or_default()
+    }
+
+    /// Bottom row, left to right
+    pub fn bottom_border(&self) -> Vec<T> {
+        self.height
+            .checked_sub(1)
+            .and_then(|y| self.row(y))
+            .map(<[T]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    /// Leftmost column, top to bottom
+    pub fn left_border(&self) -> Vec<T> {
+        self.column(0).cloned().collect()
+    }
+
+    /// Rightmost column, top to bottom
+    pub fn right_border(&self) -> Vec<T> {
+        match self.width.checked_sub(1) {
+            Some(x) => self.column(x).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Split the grid into a grid of equally sized tiles
+    pub fn split_tiles(&self, tile_width: usize, tile_height: usize) -> Result<Grid<Self>> {
+        if tile_width == 0
+            || tile_height == 0
+            || !self.width.is_multiple_of(tile_width)
+            || !self.height.is_multiple_of(tile_height)
+        {
+            return Err(anyhow!(
+                "Grid of size {}x{} can't be split into tiles of size {}x{}",
+                self.width,
+                self.height,
+                tile_width,
+                tile_height,
+            ));
+        }
+
+        let mut tiles = Vec::new();
+        for c in
+            Grid::<()>::from_fn(self.width / tile_width, self.height / tile_height, |_| ()).coords()
+        {
+            let origin = Coord::new(c.x * tile_width as isize, c.y * tile_height as isize);
+            tiles.push(self.sub_grid(&origin, tile_width, tile_height)?);
+        }
+        Grid::new(self.width / tile_width, self.height / tile_height, tiles)
+    }
+
+    /// Join a grid of equally sized tiles into a single grid. This is the inverse of
+    /// `split_tiles`.
+    pub fn stitch(tiles: &Grid<Self>) -> Result<Self> {
+        let (tile_width, tile_height) = match tiles.tiles.first() {
+            Some(t) => (t.width, t.height),
+            None => return Self::new(0, 0, Vec::new()),
+        };
+        if let Some((c, t)) = tiles
+            .iter()
+            .find(|(_, t)| t.width != tile_width || t.height != tile_height)
+        {
+            return Err(anyhow!(
+                "Tile at {:?} has size {}x{}, expected {}x{}",
+                c,
+                t.width,
+                t.height,
+                tile_width,
+                tile_height,
+            ));
+        }
+
+        let width = tiles.width * tile_width;
+        let height = tiles.height * tile_height;
+        let mut output = Vec::with_capacity(width * height);
+        for tile_row in tiles.rows() {
+            for y in 0..tile_height {
+                for tile in tile_row {
+                    output.extend_from_slice(tile.row(y).unwrap());
+                }
+            }
+        }
+        Self::new(width, height, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["abc", "def"])?;
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(grid.bounds().area(), 6);
+        assert_eq!(grid.get(&Coord::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(&Coord::new(3, 0)), None);
+        assert_eq!(grid.get(&Coord::new(0, -1)), None);
+        assert_eq!(grid.row(1), Some(['d', 'e', 'f'].as_ref()));
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(
+            grid.neighbors(&Coord::new(0, 0))
+                .map(|(_, c)| *c)
+                .collect::<String>(),
+            "bd",
+        );
+        assert_eq!(
+            grid.all_neighbors(&Coord::new(0, 0))
+                .map(|(_, c)| *c)
+                .collect::<String>(),
+            "bed",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sections() -> Result<()> {
+        let sections = parse_sections("a\nb\n\n\nc\n\n").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            sections,
+            vec![
+                Section {
+                    line_no: 1,
+                    lines: vec!["a".to_owned(), "b".to_owned()],
+                },
+                Section {
+                    line_no: 5,
+                    lines: vec!["c".to_owned()],
+                },
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_sections() -> Result<()> {
+        let (a, b): (Section, Section) = FromSections::from_sections(parse_sections("a\n\nb"))?;
+        assert_eq!(a.header(), "a");
+        assert_eq!(b.line_no, 3);
+
+        let err = <(Section, Section)>::from_sections(parse_sections("a\n\nb\n\nc"));
+        assert_eq!(err.unwrap_err().to_string(), "Expected 2 sections, found 3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_transforms() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["ab", "cd", "ef"])?;
+        let as_lines = |g: &Grid<char>| {
+            g.rows()
+                .map(|r| r.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            as_lines(&grid.transformed(Transform::rotate_right())),
+            vec!["eca", "fdb"],
+        );
+        assert_eq!(
+            as_lines(&grid.transformed(Transform::rotate_left())),
+            vec!["bdf", "ace"],
+        );
+        assert_eq!(
+            as_lines(&grid.transformed(Transform::rotate_half())),
+            vec!["fe", "dc", "ba"],
+        );
+        assert_eq!(
+            grid.view(Transform::flip_x()).get(&Coord::new(0, 0)),
+            Some(&'b'),
+        );
+
+        let all = grid.all_transforms();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], grid);
+        assert!(all[1..].iter().all(|g| *g != grid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tiles() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["abcd", "efgh", "ijkl", "mnop"])?;
+        assert_eq!(grid.crop(1)?, Grid::from_lines(["fg", "jk"])?);
+        assert_eq!(grid.left_border(), vec!['a', 'e', 'i', 'm']);
+        assert_eq!(grid.bottom_border(), vec!['m', 'n', 'o', 'p']);
+
+        let tiles = grid.split_tiles(2, 2)?;
+        assert_eq!((tiles.width, tiles.height), (2, 2));
+        assert_eq!(tiles.tiles[1], Grid::from_lines(["cd", "gh"])?);
+        assert_eq!(Grid::stitch(&tiles)?, grid);
+        assert!(grid.split_tiles(3, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_input() {
+        let input = Input::from("a\r\nb\n\n\nc\n".to_owned());
+        assert_eq!(
+            input.lines().collect::<Vec<_>>(),
+            vec!["a", "b", "", "", "c"]
+        );
+        assert_eq!(input.byte_lines().next(), Some(b"a".as_ref()));
+        assert_eq!(
+            input.records().collect::<Vec<_>>(),
+            vec![
+                Section {
+                    line_no: 1,
+                    lines: vec!["a", "b"],
+                },
+                Section {
+                    line_no: 5,
+                    lines: vec!["c"],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_template() -> Result<()> {
+        let t = Template::new("{}-{} {}: {}")?;
+        let (a, b, c, d): (usize, usize, char, String) = t.scan("1-3 a: abcde")?;
+        assert_eq!((a, b, c, d.as_str()), (1, 3, 'a', "abcde"));
+
+        let err = t
+            .scan::<(usize, usize, char, String)>("1-3 a abcde")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Column 5: expected \": \"");
+
+        let err = t
+            .scan::<(usize, usize, char, String)>("x-3 a: abcde")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Column 1: invalid value \"x\"");
+
+        let t = Template::new("{name}: {}-{} or {}-{}.")?;
+        let c = t.captures("row: 6-11 or 33-44.")?;
+        assert_eq!(c.name("name"), Some("row"));
+        assert_eq!(c.parse::<usize>(4)?, 44);
+
+        let t = Template::new("{{{}}}")?;
+        assert_eq!(t.captures("{x}")?.get(0), Some("x"));
+
+        assert!(Template::new("{}{}").is_err());
+        assert!(Template::new("{").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_edges() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["ab", "cd"])?;
+
+        let torus = grid.wrapping();
+        assert_eq!(torus.get(&Coord::new(2, 0)), Some(&'a'));
+        assert_eq!(torus.get(&Coord::new(-1, -1)), Some(&'d'));
+        assert_eq!(torus.get(&Coord::new(5, 6)), Some(&'b'));
+
+        let cylinder = grid.with_edges(Edge::Wrap, Edge::Bounded);
+        assert_eq!(cylinder.get(&Coord::new(-2, 1)), Some(&'c'));
+        assert_eq!(cylinder.get(&Coord::new(0, 2)), None);
+
+        let clamped = grid.with_edges(Edge::Clamp, Edge::Clamp);
+        assert_eq!(clamped.get(&Coord::new(10, -10)), Some(&'b'));
+
+        let filled = grid.with_edges(Edge::Fill('.'), Edge::Bounded);
+        assert_eq!(filled.get(&Coord::new(-1, 1)), Some(&'.'));
+        assert_eq!(filled.get(&Coord::new(-1, 2)), None);
+        assert_eq!(filled.all_neighbors(&Coord::new(0, 0)).count(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rays() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["#..#", "....", "..#.", "#..."])?;
+        let origin = Coord::new(0, 0);
+        assert_eq!(grid.ray(&origin, Direction::Right(1)).count(), 3);
+        assert_eq!(grid.ray(&origin, Direction::Right(2)).count(), 1);
+        assert_eq!(grid.ray(&origin, Direction::Up(0)).count(), 0);
+        assert_eq!(
+            grid.cast_ray(&origin, Direction::DownRight(1), |_, t| *t == '#'),
+            Some((Coord::new(2, 2), &'#')),
+        );
+        assert_eq!(
+            grid.cast_ray(&origin, Direction::Up(1), |_, t| *t == '#'),
+            None,
+        );
+
+        let visibility = grid.visibility(Direction::all(1), |_, t| *t == '#');
+        let mut visible = visibility.get(&origin).to_vec();
+        visible.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(
+            visible,
+            vec![Coord::new(3, 0), Coord::new(2, 2), Coord::new(0, 3)],
+        );
+        assert!(visibility.get(&Coord::new(-1, 0)).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let a = Grid::<char>::from_lines(["ab", "cd"])?;
+        let b = Grid::<char>::from_lines(["ab", "xd", "ef"])?;
+        assert_eq!(a.to_string(), "ab\ncd\n");
+        assert_eq!(
+            a.render_diff(&b),
+            "3 differences (2x2 vs 2x3)\nab | ab |\ncd | xd | X\n   | ef | XX\n",
+        );
+        assert_grid_eq!(a, a.clone());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "grids are not equal")]
+    fn test_assert_grid_eq() {
+        let a = Grid::new(1, 1, vec![true]).unwrap();
+        let b = Grid::new(1, 1, vec![false]).unwrap();
+        assert_grid_eq!(a, b);
+    }
+
+    #[test]
+    fn test_ragged_grid() {
+        let err = Grid::<char>::from_lines(["abc", "de", "fgh"]).unwrap_err();
+        assert_eq!(err.to_string(), "Line 2 has length 2, expected 3");
+    }
 }
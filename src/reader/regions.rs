@@ -0,0 +1,299 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::Grid;
+use crate::coord::hex::{Axial, Cube};
+use crate::coord::{Coord, Rect};
+
+/// Which tiles count as touching each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, right, down and left
+    Four,
+    /// Four plus the diagonals
+    Eight,
+    /// Grid coordinates are axial hex coordinates (x is q and y is r), so every tile touches 6
+    /// others
+    Hex,
+}
+
+/// Connected group of tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Sorted row by row
+    coords: Vec<Coord>,
+    connectivity: Connectivity,
+}
+
+/// Every region of a grid together with the region each tile belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regions {
+    /// Index into `regions` for every tile, None for tiles that aren't part of any region
+    pub labels: Grid<Option<usize>>,
+    pub regions: Vec<Region>,
+}
+
+impl Connectivity {
+    pub fn neighbors(&self, c: &Coord) -> Vec<Coord> {
+        match self {
+            Self::Four => c.iter_neighbors().collect(),
+            Self::Eight => c.iter_all_neighbors().collect(),
+            Self::Hex => Self::hex_neighbors(c),
+        }
+    }
+
+    fn hex_neighbors(c: &Coord) -> Vec<Coord> {
+        Cube::from(Axial::new(c.x, c.y))
+            .neighbors()
+            .map(|n| Coord::new(n.q, n.r))
+            .collect()
+    }
+
+    /// Neighbors that share a side with the tile
+    fn sides(&self, c: &Coord) -> Vec<Coord> {
+        match self {
+            Self::Four | Self::Eight => c.iter_neighbors().collect(),
+            Self::Hex => Self::hex_neighbors(c),
+        }
+    }
+
+    /// Connectivity of the tiles outside a region. A region connected by sides is only closed
+    /// off when the tiles outside it can't slip through diagonally and the other way around.
+    fn complement(&self) -> Self {
+        match self {
+            Self::Four => Self::Eight,
+            Self::Eight => Self::Four,
+            Self::Hex => Self::Hex,
+        }
+    }
+}
+
+/// Breadth first fill from the seed through the tiles accepted by include
+fn fill<F>(seed: Coord, connectivity: Connectivity, mut include: F) -> Vec<Coord>
+where
+    F: FnMut(&Coord) -> bool,
+{
+    if !include(&seed) {
+        return Vec::new();
+    }
+    let mut seen = HashSet::new();
+    seen.insert(seed);
+    let mut queue = VecDeque::new();
+    queue.push_back(seed);
+    let mut filled = Vec::new();
+    while let Some(c) = queue.pop_front() {
+        filled.push(c);
+        for n in connectivity.neighbors(&c) {
+            if !seen.contains(&n) && include(&n) {
+                seen.insert(n);
+                queue.push_back(n);
+            }
+        }
+    }
+    filled
+}
+
+impl Region {
+    fn new(mut coords: Vec<Coord>, connectivity: Connectivity) -> Self {
+        coords.sort_by_key(|c| (c.y, c.x));
+        Self {
+            coords,
+            connectivity,
+        }
+    }
+
+    pub fn coords(&self) -> &[Coord] {
+        &self.coords
+    }
+
+    pub fn size(&self) -> usize {
+        self.coords.len()
+    }
+
+    pub fn contains(&self, c: &Coord) -> bool {
+        self.coords
+            .binary_search_by_key(&(c.y, c.x), |c| (c.y, c.x))
+            .is_ok()
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::from_coords(self.coords.iter().copied())
+    }
+
+    /// Number of tile sides between the region and anything outside it, including the edge of
+    /// the grid
+    pub fn perimeter(&self) -> usize {
+        self.coords
+            .iter()
+            .map(|c| {
+                self.connectivity
+                    .sides(c)
+                    .iter()
+                    .filter(|n| !self.contains(n))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Groups of tiles outside the region that are completely enclosed by it
+    pub fn holes(&self) -> Vec<Vec<Coord>> {
+        // Everything reachable from the ring just outside the bounds isn't enclosed
+        let outer = self.bounds().pad(1);
+        let connectivity = self.connectivity.complement();
+        let mut outside = fill(outer.min, connectivity, |c| {
+            outer.contains(c) && !self.contains(c)
+        })
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+        let mut holes = Vec::new();
+        for c in self.bounds().iter() {
+            if outside.contains(&c) || self.contains(&c) {
+                continue;
+            }
+            let hole = fill(c, connectivity, |n| !self.contains(n));
+            outside.extend(hole.iter().copied());
+            holes.push(Region::new(hole, connectivity).coords);
+        }
+        holes
+    }
+}
+
+impl Regions {
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Region> {
+        self.regions.iter()
+    }
+
+    /// Region the given tile belongs to
+    pub fn region_at(&self, c: &Coord) -> Option<&Region> {
+        let label = (*self.labels.get(c)?)?;
+        self.regions.get(label)
+    }
+}
+
+impl<T> Grid<T> {
+    /// All tiles connected to the seed through tiles for which include returns true, starting
+    /// with the seed. Empty if the seed itself isn't included.
+    pub fn flood_fill<F>(
+        &self,
+        seed: &Coord,
+        connectivity: Connectivity,
+        mut include: F,
+    ) -> Vec<Coord>
+    where
+        F: FnMut(&Coord, &T) -> bool,
+    {
+        fill(*seed, connectivity, |c| {
+            self.get(c).is_some_and(|t| include(c, t))
+        })
+    }
+
+    /// Label the connected regions of tiles for which include returns true
+    pub fn regions_where<F>(&self, connectivity: Connectivity, include: F) -> Regions
+    where
+        F: Fn(&Coord, &T) -> bool,
+    {
+        self.label_regions(connectivity, |c, t, _| include(c, t))
+    }
+
+    /// Label the connected regions of equal tiles
+    pub fn regions(&self, connectivity: Connectivity) -> Regions
+    where
+        T: PartialEq,
+    {
+        self.label_regions(connectivity, |_, t, seed| t == seed)
+    }
+
+    /// Include gets the position and tile being considered and the tile the region started from
+    fn label_regions<F>(&self, connectivity: Connectivity, include: F) -> Regions
+    where
+        F: Fn(&Coord, &T, &T) -> bool,
+    {
+        let mut labels = Grid::from_fn(self.width, self.height, |_| None);
+        let mut regions = Vec::new();
+        for (c, seed) in self.iter() {
+            if labels.get(&c) != Some(&None) {
+                continue;
+            }
+            let coords = self.flood_fill(&c, connectivity, |n, t| {
+                labels.get(n) == Some(&None) && include(n, t, seed)
+            });
+            if coords.is_empty() {
+                continue;
+            }
+            for n in &coords {
+                *labels.get_mut(n).unwrap() = Some(regions.len());
+            }
+            regions.push(Region::new(coords, connectivity));
+        }
+        Regions { labels, regions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_flood_fill() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["..#..", ".#...", "#...."])?;
+        let four = grid.flood_fill(&Coord::new(0, 0), Connectivity::Four, |_, t| *t == '.');
+        assert_eq!(four.len(), 3);
+        let eight = grid.flood_fill(&Coord::new(0, 0), Connectivity::Eight, |_, t| *t == '.');
+        assert_eq!(eight.len(), 12);
+        assert!(grid
+            .flood_fill(&Coord::new(2, 0), Connectivity::Four, |_, t| *t == '.')
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_regions() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["AAAA", "BBCD", "BBCC", "EEEC"])?;
+        let regions = grid.regions(Connectivity::Four);
+        assert_eq!(regions.len(), 5);
+        let sizes = regions.iter().map(Region::size).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![4, 4, 4, 1, 3]);
+        let perimeters = regions.iter().map(Region::perimeter).collect::<Vec<_>>();
+        assert_eq!(perimeters, vec![10, 8, 10, 4, 8]);
+
+        let c = regions.region_at(&Coord::new(2, 1)).unwrap();
+        assert_eq!(c.bounds(), Rect::new(Coord::new(2, 1), Coord::new(3, 3)));
+        assert!(c.contains(&Coord::new(3, 3)));
+        assert_eq!(regions.labels.get(&Coord::new(3, 1)), Some(&Some(3)));
+
+        let walls = grid.regions_where(Connectivity::Eight, |_, t| *t == 'C' || *t == 'D');
+        assert_eq!(walls.len(), 1);
+        assert_eq!(walls.labels.get(&Coord::new(0, 0)), Some(&None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_holes() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["#####.", "#..#.#", "#####.", "#.#..."])?;
+        let regions = grid.regions_where(Connectivity::Four, |_, t| *t == '#');
+        let ring = regions.region_at(&Coord::new(0, 0)).unwrap();
+        assert_eq!(ring.holes(), vec![vec![Coord::new(1, 1), Coord::new(2, 1)]]);
+
+        // The diagonal gap only closes the hole for 8-connected regions
+        let grid = Grid::<char>::from_lines([".#.", "#.#", ".#."])?;
+        let eight = grid.regions_where(Connectivity::Eight, |_, t| *t == '#');
+        assert_eq!(eight.len(), 1);
+        assert_eq!(eight.regions[0].holes(), vec![vec![Coord::new(1, 1)]]);
+        let four = grid.regions_where(Connectivity::Four, |_, t| *t == '#');
+        assert_eq!(four.len(), 4);
+        assert!(four.iter().all(|r| r.holes().is_empty()));
+
+        let hex = grid.regions_where(Connectivity::Hex, |_, t| *t == '#');
+        assert_eq!(hex.len(), 2);
+        Ok(())
+    }
+}
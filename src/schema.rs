@@ -0,0 +1,353 @@
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::reader::Input;
+
+/// Fields that a document of key value pairs must or may contain, one per line:
+///
+/// ```text
+/// # Comment
+/// byr required int 1920-2002
+/// hgt required unit 150-193cm 59-76in
+/// hcl required regex #[0-9a-f]{6}
+/// ecl required enum amb blu brn
+/// cid optional
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub required: bool,
+    /// Any value is allowed without a rule
+    pub rule: Option<Rule>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// An integer within the range
+    Int(RangeInclusive<i64>),
+    /// Matches the whole value. The source is the pattern as written, which is anchored at both
+    /// ends when parsed.
+    Regex { source: String, regex: Regex },
+    /// An integer followed by one of the units, within the range for that unit
+    Unit(Vec<(String, RangeInclusive<i64>)>),
+    /// One of the given values
+    Enum(Vec<String>),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    #[error("missing field {0:?}")]
+    Missing(String),
+
+    #[error("invalid value {1:?} for field {0:?}")]
+    Invalid(String, String),
+
+    /// Reported, but doesn't make a document invalid
+    #[error("unknown field {0:?}")]
+    Unknown(String),
+}
+
+/// Split off the first word, dropping the whitespace around it
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.find(char::is_whitespace) {
+        Some(end) => (&s[..end], s[end..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+/// Parse ranges like "1920-2002" or "-10--5"
+fn parse_range(s: &str) -> Result<RangeInclusive<i64>> {
+    // A leading minus belongs to the lower bound
+    let split = s
+        .get(1..)
+        .and_then(|rest| rest.find('-'))
+        .ok_or_else(|| anyhow!("Expected a range like 1-5, got {:?}", s))?
+        + 1;
+    let (min, max) = (&s[..split], &s[split + 1..]);
+    Ok(min.parse()?..=max.parse()?)
+}
+
+/// Parse unit ranges like "150-193cm"
+fn parse_unit_range(s: &str) -> Result<(String, RangeInclusive<i64>)> {
+    let unit_start = s.rfind(|c: char| c.is_ascii_digit()).map_or(0, |i| i + 1);
+    let (range, unit) = s.split_at(unit_start);
+    if unit.is_empty() {
+        return Err(anyhow!("Missing unit in {:?}", s));
+    }
+    Ok((unit.to_string(), parse_range(range)?))
+}
+
+/// Split a value like "150cm" into its number and unit
+fn split_unit(value: &str) -> Option<(i64, &str)> {
+    let unit_start = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(unit_start);
+    Some((number.parse().ok()?, unit))
+}
+
+impl Violation {
+    /// Whether the violation makes a document invalid
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+}
+
+impl Rule {
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            Self::Int(range) => value.parse().is_ok_and(|v| range.contains(&v)),
+            Self::Regex { regex, .. } => regex.is_match(value),
+            Self::Unit(units) => split_unit(value).is_some_and(|(v, unit)| {
+                units
+                    .iter()
+                    .any(|(u, range)| u == unit && range.contains(&v))
+            }),
+            Self::Enum(values) => values.iter().any(|v| v == value),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    /// Parse a rule like "int 1920-2002", "unit 150-193cm 59-76in", "regex \d{9}" or
+    /// "enum amb blu"
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, args) = split_word(s);
+        Ok(match kind {
+            "int" => Self::Int(parse_range(args)?),
+            "regex" => Self::Regex {
+                source: args.to_string(),
+                regex: Regex::new(&format!("^(?:{})$", args))?,
+            },
+            "unit" => Self::Unit(
+                args.split_whitespace()
+                    .map(parse_unit_range)
+                    .collect::<Result<_>>()?,
+            ),
+            "enum" => Self::Enum(args.split_whitespace().map(|v| v.to_string()).collect()),
+            _ => return Err(anyhow!("Unknown rule {:?}", kind)),
+        })
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, rest) = split_word(s);
+        let (required, rule) = split_word(rest);
+        let required = match required {
+            "required" => true,
+            "optional" => false,
+            other => {
+                return Err(anyhow!(
+                    "Expected required or optional after {:?}, found {:?}",
+                    name,
+                    other,
+                ))
+            }
+        };
+        let rule = Some(rule)
+            .filter(|r| !r.is_empty())
+            .map(str::parse)
+            .transpose()?;
+        Ok(Self {
+            name: name.to_string(),
+            required,
+            rule,
+        })
+    }
+}
+
+impl Schema {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Input::from_path(path)?.as_str().parse()
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Every missing required field and every invalid value in schema order, followed by every
+    /// field that isn't in the schema
+    pub fn violations(&self, document: &HashMap<&str, &str>) -> Vec<Violation> {
+        let mut unknown = document
+            .keys()
+            .filter(|k| self.field(k).is_none())
+            .collect::<Vec<_>>();
+        unknown.sort_unstable();
+        self.fields
+            .iter()
+            .filter_map(|field| match document.get(field.name.as_str()) {
+                None if field.required => Some(Violation::Missing(field.name.clone())),
+                None => None,
+                Some(value) => match &field.rule {
+                    Some(rule) if !rule.is_valid(value) => {
+                        Some(Violation::Invalid(field.name.clone(), value.to_string()))
+                    }
+                    _ => None,
+                },
+            })
+            .chain(
+                unknown
+                    .into_iter()
+                    .map(|k| Violation::Unknown(k.to_string())),
+            )
+            .collect()
+    }
+
+    /// Whether all required fields are present, regardless of their values
+    pub fn is_complete(&self, document: &HashMap<&str, &str>) -> bool {
+        self.fields
+            .iter()
+            .all(|f| !f.required || document.contains_key(f.name.as_str()))
+    }
+
+    /// Whether the document has no violations other than unknown fields
+    pub fn is_valid(&self, document: &HashMap<&str, &str>) -> bool {
+        self.violations(document).iter().all(|v| !v.is_error())
+    }
+}
+
+impl FromStr for Schema {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+            .map(|(i, l)| l.parse().with_context(|| format!("Line {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { fields })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(range) => write!(f, "int {}-{}", range.start(), range.end()),
+            Self::Regex { source, .. } => write!(f, "regex {}", source),
+            Self::Unit(units) => {
+                write!(f, "unit")?;
+                for (unit, range) in units {
+                    write!(f, " {}-{}{}", range.start(), range.end(), unit)?;
+                }
+                Ok(())
+            }
+            Self::Enum(values) => write!(f, "enum {}", values.join(" ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "\
+# Test schema
+year required int 1920-2002
+height required unit 150-193cm 59-76in
+color required regex #[0-9a-f]{6}
+eyes optional enum amb blu
+note optional
+";
+
+    #[test]
+    fn test_rules() -> Result<()> {
+        let height: Rule = "unit 150-193cm 59-76in".parse()?;
+        assert!(height.is_valid("60in"));
+        assert!(height.is_valid("190cm"));
+        assert!(!height.is_valid("190in"));
+        assert!(!height.is_valid("190"));
+        assert!(!height.is_valid("cm"));
+
+        let pid: Rule = r"regex \d{9}".parse()?;
+        assert!(pid.is_valid("000000001"));
+        assert!(!pid.is_valid("0123456789"));
+        assert_eq!(pid.to_string(), r"regex \d{9}");
+
+        let unanchored = Rule::Regex {
+            source: "a".to_string(),
+            regex: Regex::new("a")?,
+        };
+        assert_eq!(unanchored.to_string(), "regex a");
+
+        let temperature: Rule = "int -10--5".parse()?;
+        assert!(temperature.is_valid("-7"));
+        assert!(!temperature.is_valid("-4"));
+        assert_eq!(temperature.to_string(), "int -10--5");
+        let offset: Rule = "int -3-3".parse()?;
+        assert!(offset.is_valid("0"));
+
+        assert!("int 5".parse::<Rule>().is_err());
+        assert!("int -5".parse::<Rule>().is_err());
+        assert!("float 1-2".parse::<Rule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema() -> Result<()> {
+        let schema: Schema = SCHEMA.parse()?;
+        assert_eq!(schema.fields().len(), 5);
+        assert!(schema.field("note").unwrap().rule.is_none());
+
+        let document = vec![
+            ("year", "2003"),
+            ("color", "#123abc"),
+            ("eyes", "red"),
+            ("extra", "1"),
+        ]
+        .into_iter()
+        .collect();
+        assert!(!schema.is_complete(&document));
+        assert_eq!(
+            schema.violations(&document),
+            vec![
+                Violation::Invalid("year".to_string(), "2003".to_string()),
+                Violation::Missing("height".to_string()),
+                Violation::Invalid("eyes".to_string(), "red".to_string()),
+                Violation::Unknown("extra".to_string()),
+            ],
+        );
+
+        let document = vec![
+            ("year", "2000"),
+            ("height", "70in"),
+            ("color", "#123abc"),
+            ("extra", "1"),
+        ]
+        .into_iter()
+        .collect();
+        assert!(schema.is_valid(&document));
+
+        let error = "year sometimes int 1-2".parse::<Schema>().unwrap_err();
+        assert_eq!(error.to_string(), "Line 1");
+
+        let spaced: Schema =
+            "  # comment\nyear  required\tint   1920-2002\n\tnote optional".parse()?;
+        assert_eq!(spaced.fields().len(), 2);
+        let year = spaced.field("year").unwrap();
+        assert!(year.required);
+        assert_eq!(year.rule.as_ref().unwrap().to_string(), "int 1920-2002");
+        assert!(spaced.field("note").unwrap().rule.is_none());
+        Ok(())
+    }
+}
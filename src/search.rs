@@ -0,0 +1,467 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::coord::Coord;
+use crate::reader::Grid;
+
+/// Result of a successful search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult<N, C> {
+    /// All nodes from the start to the goal, including both
+    pub path: Vec<N>,
+    pub cost: C,
+    /// Number of nodes that were expanded before the goal was found
+    pub num_visited: usize,
+}
+
+/// Result of a search for every shortest path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllPathsResult<N, C> {
+    pub paths: Vec<Vec<N>>,
+    pub cost: C,
+    pub num_visited: usize,
+}
+
+/// Entry in the priority queue. Ordered so that the lowest priority is popped first from the max
+/// heap.
+struct QueueEntry<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for QueueEntry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for QueueEntry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for QueueEntry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for QueueEntry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+fn reconstruct_path<N>(parents: &HashMap<N, N>, goal: N) -> Vec<N>
+where
+    N: Clone + Eq + Hash,
+{
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth first search where every step costs 1. Searching from multiple starts finds the
+/// shortest path from any of them.
+pub fn bfs<N, I, FN, IN, FG>(
+    starts: I,
+    mut neighbors: FN,
+    mut is_goal: FG,
+) -> Option<SearchResult<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if seen.insert(start.clone()) {
+            queue.push_back((start, 0));
+        }
+    }
+
+    let mut num_visited = 0;
+    while let Some((node, cost)) = queue.pop_front() {
+        num_visited += 1;
+        if is_goal(&node) {
+            return Some(SearchResult {
+                path: reconstruct_path(&parents, node),
+                cost,
+                num_visited,
+            });
+        }
+
+        for next in neighbors(&node) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Find the cheapest path using A*. Expanded nodes are never reopened, so the heuristic must be
+/// consistent: it may never decrease by more than the cost of a step, and must be zero at the
+/// goal. Otherwise the path may not be the cheapest. A heuristic that always returns zero turns
+/// this into Dijkstra's algorithm.
+pub fn astar<N, C, I, FN, IN, FH, FG>(
+    starts: I,
+    mut neighbors: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<SearchResult<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut best_costs = HashMap::new();
+    let mut closed = HashSet::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        best_costs.insert(start.clone(), C::default());
+        queue.push(QueueEntry {
+            priority: heuristic(&start),
+            cost: C::default(),
+            node: start,
+        });
+    }
+
+    while let Some(QueueEntry { cost, node, .. }) = queue.pop() {
+        // Skip stale queue entries for nodes that have already been reached more cheaply
+        if !closed.insert(node.clone()) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            return Some(SearchResult {
+                path: reconstruct_path(&parents, node),
+                cost,
+                num_visited: closed.len(),
+            });
+        }
+
+        for (next, step_cost) in neighbors(&node) {
+            let next_cost = cost + step_cost;
+            if closed.contains(&next) || best_costs.get(&next).is_some_and(|c| *c <= next_cost) {
+                continue;
+            }
+            best_costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), node.clone());
+            queue.push(QueueEntry {
+                priority: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+    None
+}
+
+/// Find the cheapest path using Dijkstra's algorithm
+pub fn dijkstra<N, C, I, FN, IN, FG>(
+    starts: I,
+    neighbors: FN,
+    is_goal: FG,
+) -> Option<SearchResult<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(starts, neighbors, |_| C::default(), is_goal)
+}
+
+/// Like `dijkstra`, but returns every path with the lowest cost. All goals reachable at the
+/// lowest cost are included.
+pub fn dijkstra_all<N, C, I, FN, IN, FG>(
+    starts: I,
+    mut neighbors: FN,
+    mut is_goal: FG,
+) -> Option<AllPathsResult<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents: HashMap<N, Vec<N>> = HashMap::new();
+    let mut best_costs = HashMap::new();
+    let mut closed = HashSet::new();
+    let mut queue = BinaryHeap::new();
+    let starts = starts.into_iter().collect::<HashSet<_>>();
+    for start in &starts {
+        best_costs.insert(start.clone(), C::default());
+        queue.push(QueueEntry {
+            priority: C::default(),
+            cost: C::default(),
+            node: start.clone(),
+        });
+    }
+
+    let mut goals = Vec::new();
+    let mut goal_cost = None;
+    while let Some(QueueEntry { cost, node, .. }) = queue.pop() {
+        if goal_cost.is_some_and(|c| cost > c) {
+            break;
+        }
+        if !closed.insert(node.clone()) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            goal_cost = Some(cost);
+            goals.push(node);
+            continue;
+        }
+
+        for (next, step_cost) in neighbors(&node) {
+            // A closed node or a start can only be reached again at the same cost through zero
+            // cost steps, and recording that parent would create a cycle or a detour in the paths
+            if closed.contains(&next) || starts.contains(&next) {
+                continue;
+            }
+            let next_cost = cost + step_cost;
+            match best_costs.get(&next) {
+                Some(c) if *c < next_cost => continue,
+                Some(c) if *c == next_cost => {
+                    parents.entry(next).or_default().push(node.clone());
+                    continue;
+                }
+                _ => {}
+            }
+            best_costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), vec![node.clone()]);
+            queue.push(QueueEntry {
+                priority: next_cost,
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+
+    // Walk backwards from every goal through all parents that reach it at the lowest cost
+    let mut paths = Vec::new();
+    let mut stack = goals.into_iter().map(|g| vec![g]).collect::<Vec<_>>();
+    while let Some(path) = stack.pop() {
+        match parents.get(path.last().unwrap()) {
+            Some(ps) => {
+                for p in ps {
+                    let mut path = path.clone();
+                    path.push(p.clone());
+                    stack.push(path);
+                }
+            }
+            None => {
+                let mut path = path;
+                path.reverse();
+                paths.push(path);
+            }
+        }
+    }
+
+    Some(AllPathsResult {
+        paths,
+        cost: goal_cost?,
+        num_visited: closed.len(),
+    })
+}
+
+/// Shortest path on a grid moving up, right, down or left through passable tiles
+pub fn grid_bfs<T, I, P>(
+    grid: &Grid<T>,
+    starts: I,
+    goal: Coord,
+    passable: P,
+) -> Option<SearchResult<Coord, usize>>
+where
+    I: IntoIterator<Item = Coord>,
+    P: Fn(&Coord, &T) -> bool,
+{
+    bfs(
+        starts,
+        |c| {
+            grid.neighbors(c)
+                .filter(|(n, t)| passable(n, t))
+                .map(|(n, _)| n)
+                .collect::<Vec<_>>()
+        },
+        |c| *c == goal,
+    )
+}
+
+/// Cheapest path on a grid moving up, right, down or left. The cost function returns the cost
+/// of entering a tile, or None if the tile is impassable.
+pub fn grid_dijkstra<T, C, I, F>(
+    grid: &Grid<T>,
+    starts: I,
+    goal: Coord,
+    cost: F,
+) -> Option<SearchResult<Coord, C>>
+where
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = Coord>,
+    F: Fn(&Coord, &T) -> Option<C>,
+{
+    dijkstra(
+        starts,
+        |c| {
+            grid.neighbors(c)
+                .filter_map(|(n, t)| Some((n, cost(&n, t)?)))
+                .collect::<Vec<_>>()
+        },
+        |c| *c == goal,
+    )
+}
+
+/// Like `grid_dijkstra`, but guided by the Manhattan distance to the goal. Every step must cost
+/// at least 1 for the result to be the cheapest path.
+pub fn grid_astar<T, I, F>(
+    grid: &Grid<T>,
+    starts: I,
+    goal: Coord,
+    cost: F,
+) -> Option<SearchResult<Coord, usize>>
+where
+    I: IntoIterator<Item = Coord>,
+    F: Fn(&Coord, &T) -> Option<usize>,
+{
+    astar(
+        starts,
+        |c| {
+            grid.neighbors(c)
+                .filter_map(|(n, t)| Some((n, cost(&n, t)?)))
+                .collect::<Vec<_>>()
+        },
+        |c| Coord::distance(*c, goal),
+        |c| *c == goal,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn maze() -> Result<Grid<char>> {
+        Grid::from_lines(["S.#.....", ".##.###.", "......#.", "#.###.#.", "......#G"])
+    }
+
+    #[test]
+    fn test_grid_bfs() -> Result<()> {
+        let grid = maze()?;
+        let result = grid_bfs(&grid, Some(Coord::new(0, 0)), Coord::new(7, 4), |_, t| {
+            *t != '#'
+        })
+        .unwrap();
+        assert_eq!(result.cost, 15);
+        assert_eq!(result.path.len(), 16);
+        assert_eq!(result.path[0], Coord::new(0, 0));
+        assert_eq!(*result.path.last().unwrap(), Coord::new(7, 4));
+        assert!(result
+            .path
+            .windows(2)
+            .all(|w| Coord::distance(w[0], w[1]) == 1));
+
+        let walled = grid_bfs(&grid, Some(Coord::new(0, 0)), Coord::new(7, 4), |_, t| {
+            *t == '.'
+        });
+        assert_eq!(walled, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_grid_weighted() -> Result<()> {
+        let grid = Grid::<char>::from_lines(["1163751", "1381373", "2136511", "3694931"])?;
+        let cost = |_: &Coord, t: &char| t.to_digit(10).map(|d| d as usize);
+        let goal = Coord::new(6, 3);
+        let a = grid_dijkstra(&grid, Some(Coord::origin()), goal, cost).unwrap();
+        let b = grid_astar(&grid, Some(Coord::origin()), goal, cost).unwrap();
+        assert_eq!(a.cost, b.cost);
+        assert!(b.num_visited <= a.num_visited);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_source() -> Result<()> {
+        let grid = maze()?;
+        let starts = vec![Coord::new(0, 0), Coord::new(7, 0)];
+        let result = grid_bfs(&grid, starts, Coord::new(7, 4), |_, t| *t != '#').unwrap();
+        assert_eq!(result.cost, 4);
+        assert_eq!(result.path[0], Coord::new(7, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph() {
+        // Two equally cheap routes from a to d and one expensive
+        let edges: HashMap<char, Vec<(char, usize)>> = vec![
+            ('a', vec![('b', 1), ('c', 2), ('d', 10)]),
+            ('b', vec![('d', 2)]),
+            ('c', vec![('d', 1)]),
+        ]
+        .into_iter()
+        .collect();
+        let neighbors = |n: &char| edges.get(n).cloned().unwrap_or_default();
+
+        let result = dijkstra(Some('a'), neighbors, |n| *n == 'd').unwrap();
+        assert_eq!(result.cost, 3);
+
+        let mut all = dijkstra_all(Some('a'), neighbors, |n| *n == 'd').unwrap();
+        all.paths.sort();
+        assert_eq!(all.cost, 3);
+        assert_eq!(all.paths, vec![vec!['a', 'b', 'd'], vec!['a', 'c', 'd']]);
+
+        assert_eq!(dijkstra(Some('d'), neighbors, |n| *n == 'a'), None);
+    }
+
+    #[test]
+    fn test_zero_cost_cycle() {
+        let edges: HashMap<char, Vec<(char, usize)>> = vec![
+            ('a', vec![('b', 0), ('c', 1)]),
+            ('b', vec![('a', 0), ('c', 1)]),
+        ]
+        .into_iter()
+        .collect();
+        let neighbors = |n: &char| edges.get(n).cloned().unwrap_or_default();
+
+        let mut all = dijkstra_all(Some('a'), neighbors, |n| *n == 'c').unwrap();
+        all.paths.sort();
+        assert_eq!(all.cost, 1);
+        assert_eq!(all.paths, vec![vec!['a', 'b', 'c'], vec!['a', 'c']]);
+    }
+
+    #[test]
+    fn test_zero_cost_starts() {
+        let edges: HashMap<char, Vec<(char, usize)>> = vec![
+            ('a', vec![('b', 0), ('c', 1)]),
+            ('b', vec![('a', 0), ('c', 1)]),
+        ]
+        .into_iter()
+        .collect();
+        let neighbors = |n: &char| edges.get(n).cloned().unwrap_or_default();
+
+        let mut all = dijkstra_all(vec!['a', 'b'], neighbors, |n| *n == 'c').unwrap();
+        all.paths.sort();
+        assert_eq!(all.cost, 1);
+        assert_eq!(all.paths, vec![vec!['a', 'c'], vec!['b', 'c']]);
+    }
+}
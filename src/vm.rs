@@ -0,0 +1,512 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Names of the registers, in the order they are stored
+pub const REGISTERS: [&str; 5] = ["acc", "a", "b", "c", "d"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// Add the value to the acc register
+    Acc,
+    /// Jump by the offset
+    Jmp,
+    /// Do nothing, the operand is ignored
+    Nop,
+    /// Set the register to the value
+    Set,
+    /// Add the value to the register
+    Add,
+    /// Multiply the register by the value
+    Mul,
+    /// Jump by the offset if the value isn't zero
+    Jnz,
+}
+
+/// What an operand may be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Register,
+    /// A register or a number
+    Value,
+    /// A number or a label, relative to the instruction
+    Offset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(usize),
+    Immediate(isize),
+}
+
+/// How an instruction is written and what operands it takes
+#[derive(Debug)]
+pub struct OpDef {
+    pub op: Op,
+    pub name: &'static str,
+    pub operands: &'static [OperandKind],
+}
+
+pub static OPS: &[OpDef] = &[
+    OpDef {
+        op: Op::Acc,
+        name: "acc",
+        operands: &[OperandKind::Value],
+    },
+    OpDef {
+        op: Op::Jmp,
+        name: "jmp",
+        operands: &[OperandKind::Offset],
+    },
+    OpDef {
+        op: Op::Nop,
+        name: "nop",
+        operands: &[OperandKind::Offset],
+    },
+    OpDef {
+        op: Op::Set,
+        name: "set",
+        operands: &[OperandKind::Register, OperandKind::Value],
+    },
+    OpDef {
+        op: Op::Add,
+        name: "add",
+        operands: &[OperandKind::Register, OperandKind::Value],
+    },
+    OpDef {
+        op: Op::Mul,
+        name: "mul",
+        operands: &[OperandKind::Register, OperandKind::Value],
+    },
+    OpDef {
+        op: Op::Jnz,
+        name: "jnz",
+        operands: &[OperandKind::Value, OperandKind::Offset],
+    },
+];
+
+/// Instructions built by hand may not fit their `OpDef`, which `Machine::step` reports as an
+/// error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Op,
+    pub operands: Vec<Operand>,
+}
+
+/// How long to run a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// Until the program halts
+    Forever,
+    /// Until the program halts or is about to run an instruction for the second time
+    StopOnRepeat,
+    /// Until the program halts or has run this many instructions
+    MaxSteps(usize),
+}
+
+/// Why a program stopped running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// The instruction pointer is just past the last instruction
+    Halted,
+    /// The instruction at the pointer has run before
+    Repeated,
+    StepLimit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Running,
+    Halted,
+}
+
+/// Runs a program one instruction at a time
+#[derive(Debug, Clone)]
+pub struct Machine<'a> {
+    program: &'a [Instruction],
+    ip: isize,
+    registers: [isize; REGISTERS.len()],
+}
+
+impl Op {
+    pub fn def(&self) -> &'static OpDef {
+        OPS.iter().find(|d| d.op == *self).unwrap()
+    }
+}
+
+impl FromStr for Op {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        OPS.iter()
+            .find(|d| d.name == s)
+            .map(|d| d.op)
+            .ok_or_else(|| anyhow!("Unknown instruction {:?}", s))
+    }
+}
+
+pub fn register_index(name: &str) -> Result<usize> {
+    REGISTERS
+        .iter()
+        .position(|r| *r == name)
+        .ok_or_else(|| anyhow!("Unknown register {:?}", name))
+}
+
+impl Operand {
+    /// Parse an operand, looking up labels in offsets relative to the instruction at `ip`
+    fn parse(s: &str, kind: OperandKind, labels: &HashMap<&str, usize>, ip: usize) -> Result<Self> {
+        if let Ok(n) = s.parse() {
+            if kind == OperandKind::Register {
+                return Err(anyhow!("Expected a register, found {:?}", s));
+            }
+            return Ok(Self::Immediate(n));
+        }
+        match kind {
+            OperandKind::Register | OperandKind::Value => Ok(Self::Register(register_index(s)?)),
+            OperandKind::Offset => match labels.get(s) {
+                Some(target) => Ok(Self::Immediate(*target as isize - ip as isize)),
+                None => Err(anyhow!("Unknown label {:?}", s)),
+            },
+        }
+    }
+}
+
+/// Whether a label is a single identifier: a letter or `_`, then letters, digits or `_`
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Instruction {
+    /// Check that the operands match the ones the op takes and that every register exists
+    pub fn validate(&self) -> Result<()> {
+        let kinds = self.op.def().operands;
+        if self.operands.len() != kinds.len() {
+            return Err(anyhow!(
+                "{} takes {} operands, found {}",
+                self.op.def().name,
+                kinds.len(),
+                self.operands.len(),
+            ));
+        }
+        for (operand, kind) in self.operands.iter().zip(kinds) {
+            match (operand, kind) {
+                (Operand::Register(r), _) if *r >= REGISTERS.len() => {
+                    return Err(anyhow!("Unknown register {}", r))
+                }
+                (Operand::Immediate(n), OperandKind::Register) => {
+                    return Err(anyhow!("Expected a register, found {}", n))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Translate source code to instructions. Every line has one instruction like `acc +1` or
+/// `set a 5`, optionally preceded by a label like `loop:`, which may also be on a line of its own.
+/// Jumps can use labels instead of offsets. Comments start with `;` or `#`.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>> {
+    let mut labels = HashMap::new();
+    let mut lines = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let mut line = line.split(&[';', '#'][..]).next().unwrap().trim();
+        if let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(anyhow!("Line {}: invalid label {:?}", i + 1, label));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(anyhow!(
+                    "Line {}: label {:?} is defined twice",
+                    i + 1,
+                    label
+                ));
+            }
+            line = rest.trim();
+        }
+        if !line.is_empty() {
+            lines.push((i + 1, line));
+        }
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(ip, (_, line))| {
+            let mut words = line.split_whitespace();
+            let op: Op = words.next().unwrap().parse()?;
+            let args = words.collect::<Vec<_>>();
+            let kinds = op.def().operands;
+            if args.len() != kinds.len() {
+                return Err(anyhow!(
+                    "{} takes {} operands, found {}",
+                    op.def().name,
+                    kinds.len(),
+                    args.len(),
+                ));
+            }
+            let operands = args
+                .iter()
+                .zip(kinds)
+                .map(|(arg, kind)| Operand::parse(arg, *kind, &labels, ip))
+                .collect::<Result<_>>()?;
+            Ok(Instruction { op, operands })
+        })
+        .enumerate()
+        .map(|(i, result)| result.with_context(|| format!("Line {}", lines[i].0)))
+        .collect()
+}
+
+/// One instruction per line, in the same format that `assemble` reads
+pub fn disassemble(program: &[Instruction]) -> String {
+    program.iter().map(|i| format!("{}\n", i)).collect()
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Self {
+            program,
+            ip: 0,
+            registers: [0; REGISTERS.len()],
+        }
+    }
+
+    /// Index of the next instruction to run
+    pub fn ip(&self) -> isize {
+        self.ip
+    }
+
+    pub fn registers(&self) -> &[isize] {
+        &self.registers
+    }
+
+    pub fn register(&self, name: &str) -> Result<isize> {
+        Ok(self.registers[register_index(name)?])
+    }
+
+    pub fn set_register(&mut self, name: &str, value: isize) -> Result<()> {
+        self.registers[register_index(name)?] = value;
+        Ok(())
+    }
+
+    /// The next instruction to run, or None if the program has halted
+    pub fn current(&self) -> Result<Option<&'a Instruction>> {
+        if self.ip == self.program.len() as isize {
+            return Ok(None);
+        }
+        match usize::try_from(self.ip)
+            .ok()
+            .and_then(|ip| self.program.get(ip))
+        {
+            Some(instruction) => Ok(Some(instruction)),
+            None => Err(anyhow!(
+                "Current instruction ({}) is out of bounds",
+                self.ip
+            )),
+        }
+    }
+
+    fn value(&self, operand: Operand) -> isize {
+        match operand {
+            Operand::Register(r) => self.registers[r],
+            Operand::Immediate(n) => n,
+        }
+    }
+
+    fn register_mut(&mut self, operand: Operand) -> Result<&mut isize> {
+        match operand {
+            Operand::Register(r) => Ok(&mut self.registers[r]),
+            Operand::Immediate(n) => Err(anyhow!("Expected a register, found {}", n)),
+        }
+    }
+
+    /// Run the next instruction
+    pub fn step(&mut self) -> Result<Step> {
+        let instruction = match self.current()? {
+            Some(instruction) => instruction,
+            None => return Ok(Step::Halted),
+        };
+        let ip = self.ip;
+        instruction
+            .validate()
+            .with_context(|| format!("Invalid instruction at {}", ip))?;
+        let args = &instruction.operands;
+        let overflow = || anyhow!("Overflow in {:?} at {}", instruction.to_string(), ip);
+        let mut jump = 1;
+        match instruction.op {
+            Op::Acc => {
+                self.registers[0] = self.registers[0]
+                    .checked_add(self.value(args[0]))
+                    .ok_or_else(overflow)?
+            }
+            Op::Jmp => jump = self.value(args[0]),
+            Op::Nop => {}
+            Op::Set => *self.register_mut(args[0])? = self.value(args[1]),
+            Op::Add => {
+                let value = self.value(args[1]);
+                let register = self.register_mut(args[0])?;
+                *register = register.checked_add(value).ok_or_else(overflow)?;
+            }
+            Op::Mul => {
+                let value = self.value(args[1]);
+                let register = self.register_mut(args[0])?;
+                *register = register.checked_mul(value).ok_or_else(overflow)?;
+            }
+            Op::Jnz => {
+                if self.value(args[0]) != 0 {
+                    jump = self.value(args[1]);
+                }
+            }
+        }
+        self.ip = self.ip.checked_add(jump).ok_or_else(overflow)?;
+        Ok(if self.ip == self.program.len() as isize {
+            Step::Halted
+        } else {
+            Step::Running
+        })
+    }
+
+    pub fn run(&mut self, mode: RunMode) -> Result<Exit> {
+        let mut visited = HashSet::new();
+        let mut num_steps = 0;
+        loop {
+            if self.current()?.is_none() {
+                return Ok(Exit::Halted);
+            }
+            match mode {
+                RunMode::StopOnRepeat if !visited.insert(self.ip) => return Ok(Exit::Repeated),
+                RunMode::MaxSteps(max) if num_steps == max => return Ok(Exit::StepLimit),
+                _ => {}
+            }
+            self.step()?;
+            num_steps += 1;
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Register(r) => write!(f, "{}", REGISTERS[*r]),
+            Self::Immediate(n) => write!(f, "{:+}", n),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.op.def().name)?;
+        for operand in &self.operands {
+            write!(f, " {}", operand)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6
+";
+
+    #[test]
+    fn test_loop_detection() -> Result<()> {
+        let program = assemble(EXAMPLE)?;
+        assert_eq!(disassemble(&program), EXAMPLE);
+
+        let mut machine = Machine::new(&program);
+        assert_eq!(machine.run(RunMode::StopOnRepeat)?, Exit::Repeated);
+        assert_eq!(machine.register("acc")?, 5);
+        assert_eq!(machine.ip(), 1);
+
+        let mut machine = Machine::new(&program);
+        assert_eq!(machine.run(RunMode::MaxSteps(3))?, Exit::StepLimit);
+        assert_eq!(machine.ip(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_labels() -> Result<()> {
+        let source = "\
+; Multiply 6 by 7 the slow way
+    set a 6
+    set b 7
+loop:
+    add acc b   # acc += b
+    add a -1
+    jnz a loop
+end: nop end
+";
+        let program = assemble(source)?;
+        assert_eq!(program[4].to_string(), "jnz a -2");
+        assert_eq!(program[5].to_string(), "nop +0");
+
+        let mut machine = Machine::new(&program);
+        assert_eq!(machine.step()?, Step::Running);
+        assert_eq!(machine.registers(), &[0, 6, 0, 0, 0]);
+        assert_eq!(machine.run(RunMode::Forever)?, Exit::Halted);
+        assert_eq!(machine.register("acc")?, 42);
+        assert_eq!(machine.step()?, Step::Halted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<()> {
+        assert!(assemble("foo +1").is_err());
+        assert!(assemble("acc").is_err());
+        assert!(assemble("set 1 2").is_err());
+        assert!(assemble("jmp nowhere").is_err());
+        assert!(assemble("a: nop +0\na: nop +0").is_err());
+        assert!(assemble(": nop +0").is_err());
+        assert!(assemble("two words: nop +0").is_err());
+        assert!(assemble("1st: nop +0").is_err());
+        assert!(assemble("_loop2: jmp _loop2").is_ok());
+        assert_eq!(
+            assemble("nop +0\nadd x 1").unwrap_err().to_string(),
+            "Line 2",
+        );
+
+        let program = assemble("jmp -1")?;
+        let error = Machine::new(&program).run(RunMode::Forever).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Current instruction (-1) is out of bounds"
+        );
+
+        let program = assemble(&format!("nop +0\njmp {}", isize::MAX))?;
+        let mut machine = Machine::new(&program);
+        machine.step()?;
+        assert!(machine.step().is_err());
+
+        for operands in [
+            vec![],
+            vec![Operand::Immediate(1), Operand::Immediate(2)],
+            vec![Operand::Register(99), Operand::Immediate(2)],
+        ] {
+            let program = vec![Instruction {
+                op: Op::Set,
+                operands,
+            }];
+            let error = Machine::new(&program).step().unwrap_err();
+            assert_eq!(error.to_string(), "Invalid instruction at 0");
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use std::path::Path;
 
-fn run_day<A, B>(day: usize, f: fn(&Path) -> Result<(A, Option<B>)>) -> Result<(A, Option<B>)> {
+/// The answers to both parts of a day, where the second one may be missing
+type Answers<A, B> = Result<(A, Option<B>)>;
+
+fn run_day<A, B>(day: usize, f: fn(&Path) -> Answers<A, B>) -> Answers<A, B> {
     f(format!("data/day{}.txt", day).as_ref())
 }
 